@@ -0,0 +1,198 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    serde::{Serialize, Deserialize},
+    near_bindgen, env, require, assert_one_yocto,
+};
+
+/// --- Access control
+/// The owner is the `owner_id` of `NonFungibleToken`,
+/// it implicitly has every role, and is transferred in two steps:
+/// the owner proposes a new owner, who then accepts it.
+/// Owner grants and revokes `Admin`,
+/// admins grant and revoke the other roles.
+/// Minters mint reserved items, treasurers distribute income.
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Owner,
+    Admin,
+    Minter,
+    PauseGuardian,
+    Treasurer,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::Admin => "admin",
+            Role::Minter => "minter",
+            Role::PauseGuardian => "pause_guardian",
+            Role::Treasurer => "treasurer",
+        }
+    }
+
+    /// the role required to grant or revoke this role
+    fn manager(&self) -> Role {
+        match self {
+            Role::Owner | Role::Admin => Role::Owner,
+            _ => Role::Admin,
+        }
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_owner(
+        &self
+    ) {
+        require!(
+            env::predecessor_account_id() == self.tokens.owner_id,
+            error::ERR_NOT_OWNER
+        );
+    }
+
+    pub(crate) fn assert_role(
+        &self,
+        role: Role
+    ) {
+        require!(
+            self.has_role(env::predecessor_account_id(), role),
+            format!("{} Require {}.", error::ERR_MISSING_ROLE, role.as_str())
+        );
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn owner(
+        &self
+    ) -> AccountId {
+        self.tokens.owner_id.clone()
+    }
+
+    pub fn pending_owner(
+        &self
+    ) -> Option<AccountId> {
+        self.pending_owner.clone()
+    }
+
+    pub fn has_role(
+        &self,
+        account_id: AccountId,
+        role: Role
+    ) -> bool {
+        if account_id == self.tokens.owner_id {
+            return true;
+        }
+
+        self.roles.get(&account_id)
+            .is_some_and(|roles| roles.contains(&role))
+    }
+
+    pub fn roles_of(
+        &self,
+        account_id: AccountId
+    ) -> Vec<Role> {
+        if account_id == self.tokens.owner_id {
+            return vec![Role::Owner];
+        }
+
+        self.roles.get(&account_id).unwrap_or_default()
+    }
+
+    #[payable]
+    pub fn grant_role(
+        &mut self,
+        account_id: AccountId,
+        role: Role
+    ) {
+        assert_one_yocto();
+        require!(role != Role::Owner, error::ERR_BAD_ROLE);
+        self.assert_role(role.manager());
+
+        let mut roles = self.roles.get(&account_id).unwrap_or_default();
+        if roles.contains(&role) {
+            return;
+        }
+        roles.push(role);
+        self.roles.insert(&account_id, &roles);
+
+        event::NearEvent::log_role_grant(
+            account_id.to_string(),
+            role.as_str().to_string(),
+            env::predecessor_account_id().to_string()
+        );
+    }
+
+    #[payable]
+    pub fn revoke_role(
+        &mut self,
+        account_id: AccountId,
+        role: Role
+    ) {
+        assert_one_yocto();
+        require!(role != Role::Owner, error::ERR_BAD_ROLE);
+        self.assert_role(role.manager());
+
+        let mut roles = self.roles.get(&account_id).unwrap_or_default();
+        if !roles.contains(&role) {
+            return;
+        }
+        roles.retain(|r| *r != role);
+        if roles.is_empty() {
+            self.roles.remove(&account_id);
+        } else {
+            self.roles.insert(&account_id, &roles);
+        }
+
+        event::NearEvent::log_role_revoke(
+            account_id.to_string(),
+            role.as_str().to_string(),
+            env::predecessor_account_id().to_string()
+        );
+    }
+
+    /// first step of ownership transfer,
+    /// `new_owner_id` has to accept it
+    #[payable]
+    pub fn transfer_ownership(
+        &mut self,
+        new_owner_id: AccountId
+    ) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        self.pending_owner = Some(new_owner_id.clone());
+
+        event::NearEvent::log_ownership_transfer_start(
+            self.tokens.owner_id.to_string(),
+            new_owner_id.to_string()
+        );
+    }
+
+    #[payable]
+    pub fn accept_ownership(
+        &mut self
+    ) {
+        assert_one_yocto();
+        let new_owner_id = env::predecessor_account_id();
+        require!(
+            self.pending_owner.as_ref() == Some(&new_owner_id),
+            error::ERR_NOT_PENDING_OWNER
+        );
+
+        let old_owner_id = std::mem::replace(
+            &mut self.tokens.owner_id,
+            new_owner_id.clone()
+        );
+        self.pending_owner = None;
+
+        event::NearEvent::log_ownership_transfer(
+            old_owner_id.to_string(),
+            new_owner_id.to_string()
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use crate::*;
+use near_sdk::{
+    serde::{Serialize, Deserialize},
+    collections::UnorderedSet,
+    near_bindgen, env, require, Promise,
+};
+use near_contract_standards::non_fungible_token::approval::ext_approval_receiver;
+use std::collections::HashMap;
+
+/// --- Approval
+/// Approvals are logged as events, and indexed by approved account,
+/// so marketplaces can find every token they are approved for.
+/// The index is kept in sync wherever approvals are cleared:
+/// revoke, transfer, a reverted `nft_transfer_call` and burn.
+/// `nft_approve` charges the approver for storage of the index too.
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ApprovalView {
+    pub token_id: TokenId,
+    pub owner_id: AccountId,
+    pub approval_id: u64,
+}
+
+impl Contract {
+    pub(crate) fn index_approval(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &TokenId
+    ) {
+        let mut token_ids = self.approvals_by_account.get(account_id)
+            .unwrap_or_else(|| {
+                UnorderedSet::new(StorageKey::ApprovalsByAccountInner {
+                    account_hash: env::sha256(account_id.as_bytes()),
+                })
+            });
+        token_ids.insert(token_id);
+        self.approvals_by_account.insert(account_id, &token_ids);
+    }
+
+    pub(crate) fn unindex_approval(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &TokenId
+    ) {
+        if let Some(mut token_ids) = self.approvals_by_account.get(account_id) {
+            token_ids.remove(token_id);
+            if token_ids.is_empty() {
+                self.approvals_by_account.remove(account_id);
+            } else {
+                self.approvals_by_account.insert(account_id, &token_ids);
+            }
+        }
+    }
+
+    pub(crate) fn unindex_approvals(
+        &mut self,
+        token_id: &TokenId,
+        approvals: Option<&HashMap<AccountId, u64>>
+    ) {
+        for account_id in approvals.into_iter().flat_map(|approvals| approvals.keys()) {
+            self.unindex_approval(account_id, token_id);
+        }
+    }
+
+    pub(crate) fn approvals_of(
+        &self,
+        token_id: &TokenId
+    ) -> Option<HashMap<AccountId, u64>> {
+        self.tokens.approvals_by_id.as_ref()
+            .and_then(|by_id| by_id.get(token_id))
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenApproval for Contract {
+    #[payable]
+    fn nft_approve(
+        &mut self,
+        token_id: TokenId,
+        account_id: AccountId,
+        msg: Option<String>,
+    ) -> Option<Promise> {
+        require!(
+            env::attached_deposit() >= 1,
+            error::ERR_NO_ENOUGH_STORAGE_DEPOSIT
+        );
+        self.assert_not_paused(PauseTarget::Approval);
+        self.assert_not_soulbound();
+
+        let owner_id = self.owner_of(&token_id)
+            .expect(error::ERR_TOKEN_NOT_EXIST);
+        require!(
+            owner_id == env::predecessor_account_id(),
+            error::ERR_NOT_TOKEN_OWNER
+        );
+
+        let init_storage_usage = env::storage_usage();
+
+        let mut approvals = self.approvals_of(&token_id).unwrap_or_default();
+        let next_approval_id_by_id = self.tokens.next_approval_id_by_id.as_mut().unwrap();
+        let approval_id = next_approval_id_by_id.get(&token_id).unwrap_or(1);
+        next_approval_id_by_id.insert(&token_id, &(approval_id + 1));
+
+        approvals.insert(account_id.clone(), approval_id);
+        self.tokens.approvals_by_id.as_mut().unwrap()
+            .insert(&token_id, &approvals);
+        self.index_approval(&account_id, &token_id);
+
+        self.refund_storage_deposit(
+            init_storage_usage,
+            env::attached_deposit(),
+            &owner_id
+        );
+
+        event::NearEvent::log_nft_approve(
+            owner_id.to_string(),
+            token_id.clone(),
+            account_id.to_string(),
+            approval_id
+        );
+
+        msg.map(|msg| {
+            ext_approval_receiver::nft_on_approve(
+                token_id,
+                owner_id,
+                approval_id,
+                msg,
+                account_id,
+                NO_DEPOSIT,
+                env::prepaid_gas() - GAS_FOR_NFT_APPROVE
+            )
+        })
+    }
+
+    #[payable]
+    fn nft_revoke(
+        &mut self,
+        token_id: TokenId,
+        account_id: AccountId
+    ) {
+        let was_approved = self.approvals_of(&token_id)
+            .is_some_and(|approvals| approvals.contains_key(&account_id));
+
+        self.tokens.nft_revoke(token_id.clone(), account_id.clone());
+
+        if was_approved {
+            self.unindex_approval(&account_id, &token_id);
+            event::NearEvent::log_nft_revoke(
+                env::predecessor_account_id().to_string(),
+                token_id,
+                account_id.to_string()
+            );
+        }
+    }
+
+    #[payable]
+    fn nft_revoke_all(
+        &mut self,
+        token_id: TokenId
+    ) {
+        let approvals = self.approvals_of(&token_id);
+
+        self.tokens.nft_revoke_all(token_id.clone());
+
+        if approvals.is_some() {
+            self.unindex_approvals(&token_id, approvals.as_ref());
+            event::NearEvent::log_nft_revoke_all(
+                env::predecessor_account_id().to_string(),
+                token_id
+            );
+        }
+    }
+
+    fn nft_is_approved(
+        &self,
+        token_id: TokenId,
+        approved_account_id: AccountId,
+        approval_id: Option<u64>,
+    ) -> bool {
+        self.tokens.nft_is_approved(token_id, approved_account_id, approval_id)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// tokens an account, usually a marketplace, is approved for
+    pub fn approvals_by_account(
+        &self,
+        account_id: AccountId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<ApprovalView> {
+        let token_ids = match self.approvals_by_account.get(&account_id) {
+            Some(token_ids) => token_ids,
+            None => return vec![],
+        };
+
+        token_ids.iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(u64::MAX) as usize)
+            .filter_map(|token_id| {
+                let owner_id = self.owner_of(&token_id)?;
+                let approval_id = *self.approvals_of(&token_id)?.get(&account_id)?;
+
+                Some(ApprovalView {
+                    token_id,
+                    owner_id,
+                    approval_id,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{context, setup_token, mint, set_predecessor};
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+
+    fn approve(contract: &mut Contract, token_id: &str, account_id: AccountId) {
+        testing_env!(context(accounts(1))
+            .attached_deposit(10u128.pow(22))
+            .build());
+        contract.nft_approve(token_id.to_string(), account_id, None);
+    }
+
+    fn approved_tokens(contract: &Contract, account_id: AccountId) -> Vec<TokenId> {
+        contract.approvals_by_account(account_id, None, None)
+            .into_iter()
+            .map(|approval| approval.token_id)
+            .collect()
+    }
+
+    #[test]
+    fn approve_and_revoke() {
+        let mut contract = setup_token();
+        mint(&mut contract, "1", accounts(1));
+
+        approve(&mut contract, "0", accounts(4));
+        assert_eq!(
+            get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"mystery_box","version":"1.0.0","event":"nft_approve","data":[{"owner_id":"bob","token_id":"0","account_id":"eugene","approval_id":1}]}"#
+        );
+        approve(&mut contract, "1", accounts(4));
+
+        let mut token_ids = approved_tokens(&contract, accounts(4));
+        token_ids.sort();
+        assert_eq!(vec!["0", "1"], token_ids);
+
+        let page = contract.approvals_by_account(accounts(4), Some(1), Some(1));
+        assert_eq!(1, page.len());
+        assert_eq!(accounts(1), page[0].owner_id);
+
+        set_predecessor(accounts(1));
+        contract.nft_revoke("0".to_string(), accounts(4));
+        assert_eq!(
+            get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"mystery_box","version":"1.0.0","event":"nft_revoke","data":[{"owner_id":"bob","token_id":"0","account_id":"eugene"}]}"#
+        );
+        assert_eq!(vec!["1"], approved_tokens(&contract, accounts(4)));
+
+        contract.nft_revoke_all("1".to_string());
+        assert!(approved_tokens(&contract, accounts(4)).is_empty());
+    }
+
+    #[test]
+    fn transfer_clears_index() {
+        let mut contract = setup_token();
+        approve(&mut contract, "0", accounts(4));
+
+        set_predecessor(accounts(4));
+        contract.nft_transfer(accounts(2), "0".to_string(), Some(1), None);
+
+        assert!(approved_tokens(&contract, accounts(4)).is_empty());
+    }
+}
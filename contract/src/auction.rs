@@ -0,0 +1,628 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    serde::{Serialize, Deserialize},
+    json_types::{U128, U64},
+    near_bindgen, env, require, ext_contract,
+    Balance, Promise, PromiseResult, Timestamp,
+};
+
+/// --- Auction
+/// Rare items can be sold in an english auction instead of the raffle.
+/// Creating an auction takes a reserved item out of the raffle.
+/// The first bid must be at least `reserve_price`, which covers storage
+/// of the token, and every later bid must beat the highest one
+/// by `min_increment`.
+/// A bid within `extension` of the end pushes the end back to
+/// `extension` after that bid, so there is always time to respond.
+/// Outbid bidders are refunded right away. If a refund fails,
+/// it's kept for the bidder to withdraw later, and a failed
+/// withdrawal is kept again.
+/// After the end anyone can settle: the item is minted to the winner,
+/// and the winning bid less storage of the token counts as primary
+/// income. Without a bid the item goes back to the reserve.
+/// Once the sale has failed no auction can be bid on or settled,
+/// anyone can cancel it instead to refund the highest bid.
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Bid {
+    pub bidder_id: AccountId,
+    pub amount: U128,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Auction {
+    pub index: u64,
+    pub reserve_price: U128,
+    pub min_increment: U128,
+    pub start_at: U64,
+    pub end_at: U64,
+    pub extension: U64,
+    pub highest_bid: Option<Bid>,
+}
+
+impl Auction {
+    fn is_active(
+        &self,
+        now: Timestamp
+    ) -> bool {
+        now >= self.start_at.0 && now < self.end_at.0
+    }
+
+    fn min_bid(
+        &self
+    ) -> Balance {
+        match &self.highest_bid {
+            Some(bid) => bid.amount.0.saturating_add(self.min_increment.0),
+            None => self.reserve_price.0,
+        }
+    }
+
+    /// the end after a bid at `now`, extended if it's too close
+    fn end_after_bid(
+        &self,
+        now: Timestamp
+    ) -> Timestamp {
+        self.end_at.0.max(now.saturating_add(self.extension.0))
+    }
+}
+
+#[ext_contract(ext_auction)]
+trait AuctionCallback {
+    fn on_bid_refund(
+        &mut self,
+        bidder_id: AccountId,
+        amount: U128,
+    );
+}
+
+impl Contract {
+    /// send a bid back to its bidder,
+    /// keep it for withdrawal if the transfer fails
+    fn refund_bid(
+        &self,
+        bidder_id: AccountId,
+        amount: Balance
+    ) {
+        Promise::new(bidder_id.clone())
+            .transfer(amount)
+            .then(ext_auction::on_bid_refund(
+                bidder_id,
+                amount.into(),
+                env::current_account_id(),
+                NO_DEPOSIT,
+                GAS_FOR_BID_REFUND
+            ));
+    }
+
+    /// remove an auction that ended without a winner,
+    /// and put its item back into the reserve
+    fn close_auction(
+        &mut self,
+        index: u64
+    ) {
+        self.auctions.remove(&index);
+        self.raffle.insert(index);
+        self.reserved += 1;
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// put a reserved item up for auction
+    pub fn create_auction(
+        &mut self,
+        index: u64,
+        reserve_price: U128,
+        min_increment: U128,
+        start_at: Option<U64>,
+        duration: U64,
+        extension: U64,
+    ) {
+        self.assert_role(Role::Minter);
+        require!(
+            min_increment.0 > 0 && duration.0 > 0,
+            error::ERR_BAD_AUCTION_CONFIG
+        );
+        // the winning bid pays for storage of the token
+        require!(
+            reserve_price.0 >= self.storage_cost_for(1),
+            error::ERR_BAD_AUCTION_CONFIG
+        );
+
+        require!(self.reserved > 0, error::ERR_NO_ENOUGH_RESERVED);
+        self.reserved -= 1;
+        self.raffle.take(index);
+
+        let start_at = start_at.map_or(env::block_timestamp(), |t| t.0);
+        let auction = Auction {
+            index,
+            reserve_price,
+            min_increment,
+            start_at: start_at.into(),
+            end_at: start_at.saturating_add(duration.0).into(),
+            extension,
+            highest_bid: None,
+        };
+        self.auctions.insert(&index, &auction);
+
+        event::NearEvent::log_auction_create(
+            index,
+            reserve_price.0.to_string(),
+            auction.start_at.0,
+            auction.end_at.0
+        );
+    }
+
+    /// bid with the attached deposit
+    #[payable]
+    pub fn bid(
+        &mut self,
+        index: u64
+    ) {
+        require!(
+            self.escrow_status() != escrow::EscrowStatus::Failed,
+            error::ERR_SALE_FAILED
+        );
+
+        let mut auction = self.auctions.get(&index)
+            .expect(error::ERR_NO_AUCTION);
+        let now = env::block_timestamp();
+        require!(auction.is_active(now), error::ERR_AUCTION_NOT_ACTIVE);
+
+        let amount = env::attached_deposit();
+        require!(
+            amount >= auction.min_bid(),
+            format!(
+                "{} Require {}.",
+                error::ERR_BID_TOO_LOW,
+                auction.min_bid(),
+            )
+        );
+
+        let bidder_id = env::predecessor_account_id();
+        let outbid = auction.highest_bid.replace(Bid {
+            bidder_id: bidder_id.clone(),
+            amount: amount.into(),
+        });
+        auction.end_at = auction.end_after_bid(now).into();
+        self.auctions.insert(&index, &auction);
+
+        if let Some(outbid) = outbid {
+            self.refund_bid(outbid.bidder_id, outbid.amount.0);
+        }
+
+        event::NearEvent::log_auction_bid(
+            index,
+            bidder_id.to_string(),
+            amount.to_string(),
+            auction.end_at.0
+        );
+    }
+
+    /// keep the refund for bidder to withdraw if it failed
+    #[private]
+    pub fn on_bid_refund(
+        &mut self,
+        bidder_id: AccountId,
+        amount: U128
+    ) {
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            return;
+        }
+
+        let refund = self.bid_refunds.get(&bidder_id).unwrap_or(0);
+        self.bid_refunds.insert(&bidder_id, &(refund + amount.0));
+    }
+
+    pub fn withdraw_bid_refund(
+        &mut self
+    ) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let refund = self.bid_refunds.remove(&account_id)
+            .expect(error::ERR_NO_BID_REFUND);
+
+        self.refund_bid(account_id, refund);
+
+        refund.into()
+    }
+
+    /// mint the item to the winner once the auction has ended,
+    /// an item without bid is put back into the reserve
+    pub fn settle_auction(
+        &mut self,
+        index: u64
+    ) -> Option<Token> {
+        require!(
+            self.escrow_status() != escrow::EscrowStatus::Failed,
+            error::ERR_SALE_FAILED
+        );
+
+        let auction = self.auctions.get(&index)
+            .expect(error::ERR_NO_AUCTION);
+        require!(
+            env::block_timestamp() >= auction.end_at.0,
+            error::ERR_AUCTION_NOT_ENDED
+        );
+
+        let (receiver_id, amount) = match &auction.highest_bid {
+            Some(bid) => (bid.bidder_id.clone(), bid.amount.0),
+            None => {
+                self.close_auction(index);
+
+                event::NearEvent::log_auction_settle(index, None, "0".to_string());
+                return None;
+            }
+        };
+        self.auctions.remove(&index);
+
+        let init_storage_usage = env::storage_usage();
+        let token = self.mint_to(&index, &receiver_id);
+
+        event::NearEvent::log_nft_mint(
+            receiver_id.to_string(),
+            vec![index.to_string()],
+            None
+        );
+        mint::log_reveal(std::slice::from_ref(&token));
+
+        // storage of the token is paid out of the winning bid,
+        // which is at least the reserve price
+        let storage_cost = env::storage_byte_cost() *
+            Balance::from(env::storage_usage() - init_storage_usage);
+        self.total_income += amount - storage_cost;
+
+        event::NearEvent::log_auction_settle(
+            index,
+            auction.highest_bid.map(|bid| bid.bidder_id.to_string()),
+            amount.to_string()
+        );
+
+        Some(token)
+    }
+
+    /// cancel an auction once the sale has failed,
+    /// its highest bid is refunded and the item goes back to the reserve
+    pub fn cancel_auction(
+        &mut self,
+        index: u64
+    ) {
+        require!(
+            self.escrow_status() == escrow::EscrowStatus::Failed,
+            error::ERR_SALE_NOT_FAILED
+        );
+
+        let auction = self.auctions.get(&index)
+            .expect(error::ERR_NO_AUCTION);
+        self.close_auction(index);
+
+        if let Some(bid) = auction.highest_bid {
+            self.refund_bid(bid.bidder_id, bid.amount.0);
+        }
+
+        event::NearEvent::log_auction_settle(index, None, "0".to_string());
+    }
+
+    pub fn auction(
+        &self,
+        index: u64
+    ) -> Option<Auction> {
+        self.auctions.get(&index)
+    }
+
+    pub fn auctions(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<Auction> {
+        self.auctions.values()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(u64::MAX) as usize)
+            .collect()
+    }
+
+    /// refund of an outbid bid that failed to be sent back
+    pub fn bid_refund_of(
+        &self,
+        account_id: AccountId
+    ) -> U128 {
+        self.bid_refunds.get(&account_id).unwrap_or(0).into()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::escrow::{Escrow, EscrowConfig};
+    use crate::test_utils::{context, setup, set_predecessor};
+    use near_sdk::test_utils::{accounts, get_created_receipts};
+    use near_sdk::mock::VmAction;
+    use near_sdk::{testing_env, RuntimeFeesConfig, VMConfig};
+
+    const BID: Balance = 10u128.pow(24);
+
+    fn call_at(
+        predecessor: AccountId,
+        deposit: Balance,
+        now: Timestamp
+    ) {
+        testing_env!(context(predecessor)
+            .attached_deposit(deposit)
+            .block_timestamp(now)
+            .build());
+    }
+
+    /// item #5 up for auction between 1000 and 2000
+    fn setup_auction() -> Contract {
+        let mut contract = setup();
+        contract.reserved = 1;
+
+        set_predecessor(accounts(0));
+        contract.create_auction(5, U128(BID), U128(10), Some(U64(1000)), U64(1000), U64(300));
+        contract
+    }
+
+    /// a sale that fails at 1800, before the auction ends
+    fn fail_sale_at_1800(contract: &mut Contract) {
+        contract.escrow = Some(Escrow::new(EscrowConfig {
+            threshold: 5,
+            deadline: U64(1800),
+        }));
+    }
+
+    /// every NEAR transfer made so far, by receiver
+    fn transfers() -> Vec<(AccountId, Balance)> {
+        get_created_receipts().into_iter()
+            .flat_map(|receipt| {
+                let receiver_id = receipt.receiver_id;
+                receipt.actions.into_iter().filter_map(move |action| match action {
+                    VmAction::Transfer { deposit } => Some((receiver_id.clone(), deposit)),
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    fn refund_with(
+        contract: &mut Contract,
+        result: PromiseResult
+    ) {
+        testing_env!(
+            context(accounts(0)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result]
+        );
+        contract.on_bid_refund(accounts(2), U128(100));
+    }
+
+    #[test]
+    fn bid() {
+        let mut contract = setup_auction();
+        assert_eq!(0, contract.reserved);
+        assert_eq!(9, contract.raffle.items_left());
+
+        call_at(accounts(2), BID, 1500);
+        contract.bid(5);
+
+        let bid = contract.auction(5).unwrap().highest_bid.unwrap();
+        assert_eq!(accounts(2), bid.bidder_id);
+        assert_eq!(BID, bid.amount.0);
+        assert!(transfers().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "E51: bad auction config.")]
+    fn reserve_price_below_storage() {
+        let mut contract = setup();
+        contract.reserved = 1;
+
+        set_predecessor(accounts(0));
+        contract.create_auction(5, U128(100), U128(10), None, U64(1000), U64(300));
+    }
+
+    #[test]
+    #[should_panic(expected = "E49: bid too low.")]
+    fn bid_too_low() {
+        let mut contract = setup_auction();
+
+        call_at(accounts(2), BID, 1500);
+        contract.bid(5);
+
+        call_at(accounts(3), BID + 9, 1500);
+        contract.bid(5);
+    }
+
+    #[test]
+    #[should_panic(expected = "E48: auction not active.")]
+    fn bid_after_end() {
+        let mut contract = setup_auction();
+
+        call_at(accounts(2), BID, 2000);
+        contract.bid(5);
+    }
+
+    #[test]
+    #[should_panic(expected = "E34: sale failed.")]
+    fn bid_after_sale_failed() {
+        let mut contract = setup_auction();
+        fail_sale_at_1800(&mut contract);
+
+        call_at(accounts(2), BID, 1800);
+        contract.bid(5);
+    }
+
+    #[test]
+    fn outbid_refund() {
+        let mut contract = setup_auction();
+
+        call_at(accounts(2), BID, 1500);
+        contract.bid(5);
+        call_at(accounts(3), BID + 10, 1800);
+        contract.bid(5);
+
+        let auction = contract.auction(5).unwrap();
+        assert_eq!(accounts(3), auction.highest_bid.unwrap().bidder_id);
+        assert_eq!(2100, auction.end_at.0);
+        assert_eq!(vec![(accounts(2), BID)], transfers());
+    }
+
+    #[test]
+    fn on_bid_refund() {
+        let mut contract = setup_auction();
+
+        refund_with(&mut contract, PromiseResult::Successful(vec![]));
+        assert_eq!(0, contract.bid_refund_of(accounts(2)).0);
+
+        refund_with(&mut contract, PromiseResult::Failed);
+        refund_with(&mut contract, PromiseResult::Failed);
+        assert_eq!(200, contract.bid_refund_of(accounts(2)).0);
+    }
+
+    #[test]
+    fn withdraw_bid_refund() {
+        let mut contract = setup_auction();
+        refund_with(&mut contract, PromiseResult::Failed);
+
+        call_at(accounts(2), 0, 0);
+        assert_eq!(100, contract.withdraw_bid_refund().0);
+        assert_eq!(0, contract.bid_refund_of(accounts(2)).0);
+        assert_eq!(vec![(accounts(2), 100)], transfers());
+
+        // failed again, kept for the next try
+        refund_with(&mut contract, PromiseResult::Failed);
+        assert_eq!(100, contract.bid_refund_of(accounts(2)).0);
+    }
+
+    #[test]
+    #[should_panic(expected = "E52: no bid refund.")]
+    fn withdraw_no_bid_refund() {
+        let mut contract = setup_auction();
+
+        call_at(accounts(2), 0, 0);
+        contract.withdraw_bid_refund();
+    }
+
+    #[test]
+    fn settle_auction() {
+        let mut contract = setup_auction();
+
+        call_at(accounts(2), BID, 1500);
+        contract.bid(5);
+
+        call_at(accounts(3), 0, 2000);
+        let token = contract.settle_auction(5).unwrap();
+
+        assert_eq!("5", token.token_id);
+        assert_eq!(accounts(2), contract.owner_of(&token.token_id).unwrap());
+        assert!(contract.auction(5).is_none());
+        // storage is paid out of the bid
+        assert!(contract.total_income > 0 && contract.total_income < BID);
+    }
+
+    #[test]
+    fn settle_auction_without_bid() {
+        let mut contract = setup_auction();
+
+        call_at(accounts(3), 0, 2000);
+        assert!(contract.settle_auction(5).is_none());
+
+        assert!(contract.owner_of(&"5".to_string()).is_none());
+        assert!(contract.auction(5).is_none());
+        assert_eq!(1, contract.reserved);
+        assert_eq!(10, contract.raffle.items_left());
+        assert_eq!(0, contract.total_income);
+    }
+
+    #[test]
+    #[should_panic(expected = "E50: auction not ended.")]
+    fn settle_auction_not_ended() {
+        let mut contract = setup_auction();
+
+        call_at(accounts(3), 0, 1999);
+        contract.settle_auction(5);
+    }
+
+    #[test]
+    #[should_panic(expected = "E34: sale failed.")]
+    fn settle_auction_after_sale_failed() {
+        let mut contract = setup_auction();
+        fail_sale_at_1800(&mut contract);
+
+        call_at(accounts(2), BID, 1500);
+        contract.bid(5);
+
+        call_at(accounts(3), 0, 2000);
+        contract.settle_auction(5);
+    }
+
+    #[test]
+    fn cancel_auction_after_sale_failed() {
+        let mut contract = setup_auction();
+        fail_sale_at_1800(&mut contract);
+
+        call_at(accounts(2), BID, 1500);
+        contract.bid(5);
+
+        call_at(accounts(3), 0, 1800);
+        contract.cancel_auction(5);
+
+        assert!(contract.auction(5).is_none());
+        assert_eq!(1, contract.reserved);
+        assert_eq!(10, contract.raffle.items_left());
+        assert_eq!(vec![(accounts(2), BID)], transfers());
+    }
+
+    #[test]
+    #[should_panic(expected = "E36: sale not failed.")]
+    fn cancel_auction_before_sale_failed() {
+        let mut contract = setup_auction();
+
+        call_at(accounts(3), 0, 1500);
+        contract.cancel_auction(5);
+    }
+
+    fn auction(highest_bid: Option<Balance>) -> Auction {
+        Auction {
+            index: 0,
+            reserve_price: U128(100),
+            min_increment: U128(10),
+            start_at: U64(1000),
+            end_at: U64(2000),
+            extension: U64(300),
+            highest_bid: highest_bid.map(|amount| Bid {
+                bidder_id: "alice".parse().unwrap(),
+                amount: U128(amount),
+            }),
+        }
+    }
+
+    #[test]
+    fn active_window() {
+        let auction = auction(None);
+        assert!(!auction.is_active(999));
+        assert!(auction.is_active(1000));
+        assert!(auction.is_active(1999));
+        assert!(!auction.is_active(2000));
+    }
+
+    #[test]
+    fn min_bid() {
+        assert_eq!(100, auction(None).min_bid());
+        assert_eq!(160, auction(Some(150)).min_bid());
+    }
+
+    #[test]
+    fn anti_sniping() {
+        let auction = auction(Some(150));
+        // far from the end, nothing changes
+        assert_eq!(2000, auction.end_after_bid(1500));
+        // within extension of the end, extended
+        assert_eq!(2100, auction.end_after_bid(1800));
+        assert_eq!(2299, auction.end_after_bid(1999));
+    }
+}
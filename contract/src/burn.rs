@@ -0,0 +1,71 @@
+use crate::*;
+use near_sdk::{
+    near_bindgen, env, require, assert_one_yocto,
+};
+
+impl Contract {
+    /// remove a token and everything stored with it,
+    /// logging should be handled by caller
+    pub(crate) fn internal_burn(
+        &mut self,
+        token_id: &TokenId,
+        owner_id: &AccountId
+    ) {
+        self.tokens.owner_by_id.remove(token_id);
+
+        if let Some(token_metadata_by_id) = &mut self.tokens.token_metadata_by_id {
+            token_metadata_by_id.remove(token_id);
+        }
+
+        if let Some(tokens_per_owner) = &mut self.tokens.tokens_per_owner {
+            if let Some(mut owner_tokens) = tokens_per_owner.get(owner_id) {
+                owner_tokens.remove(token_id);
+                if owner_tokens.is_empty() {
+                    tokens_per_owner.remove(owner_id);
+                } else {
+                    tokens_per_owner.insert(owner_id, &owner_tokens);
+                }
+            }
+        }
+
+        let approvals = self.tokens.approvals_by_id.as_mut()
+            .and_then(|approvals_by_id| approvals_by_id.remove(token_id));
+        self.unindex_approvals(token_id, approvals.as_ref());
+        self.cancel_listing(token_id);
+        if let Some(next_approval_id_by_id) = &mut self.tokens.next_approval_id_by_id {
+            next_approval_id_by_id.remove(token_id);
+        }
+
+        // income held for its refund is released
+        self.take_escrow_payment(token_id);
+        self.purchase_by_token.remove(token_id);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// token owner destroys their token
+    #[payable]
+    pub fn nft_burn(
+        &mut self,
+        token_id: TokenId
+    ) {
+        assert_one_yocto();
+
+        let owner_id = self.owner_of(&token_id)
+            .expect(error::ERR_TOKEN_NOT_EXIST);
+        require!(
+            owner_id == env::predecessor_account_id(),
+            error::ERR_NOT_TOKEN_OWNER
+        );
+        self.assert_not_staked(&token_id);
+
+        self.internal_burn(&token_id, &owner_id);
+        event::NearEvent::log_nft_burn(
+            owner_id.to_string(),
+            vec![token_id],
+            None,
+            None
+        );
+    }
+}
@@ -1,27 +1,58 @@
 use crate::*;
 
 use near_sdk::{
+    json_types::U128,
     near_bindgen, env, require, Balance, Promise
 };
 use near_contract_standards::non_fungible_token::{
     Token
 };
-use near_units::{parse_near};
 
 /// cost related functions
 #[near_bindgen]
 impl Contract {
+    /// items left for public sale,
+    /// excluding the ones reserved by owner
+    pub fn items_for_sale(
+        &self
+    ) -> u64 {
+        self.raffle.items_left().saturating_sub(self.reserved)
+    }
+
     pub fn unit_price(
         &self
     ) -> Balance {
-        parse_near!("1N")
+        self.price_schedule.price_at(
+            env::block_timestamp(),
+            self.raffle.items_drawn()
+        )
     }
 
+    /// the amount actually charged for minting N tokens,
+    /// with volume discount applied
     pub(crate) fn mint_cost_for(
         &self,
         n: u64
     ) -> Balance {
-        Balance::from(n) * self.unit_price()
+        let cost = self.price_schedule.cost_at(
+            env::block_timestamp(),
+            self.raffle.items_drawn(),
+            n
+        );
+
+        pricing::apply_discount(&self.discount_tiers, n, cost)
+    }
+
+    pub(crate) fn storage_cost_for(
+        &self,
+        n: u64
+    ) -> Balance {
+        let storage_cost_per_token = env::storage_byte_cost() *
+            (self.tokens.extra_storage_in_bytes_per_token + STORAGE_PER_PURCHASED_TOKEN) as Balance;
+        let storage_cost_per_purchase = env::storage_byte_cost() *
+            STORAGE_PER_PURCHASE as Balance;
+
+        storage_cost_per_token * n as Balance + storage_cost_per_purchase
     }
 
     /// return the cost for buying N tokens
@@ -30,13 +61,7 @@ impl Contract {
         &self,
         n: u64
     ) -> Balance {
-        let mint_cost = self.mint_cost_for(n);
-
-        let storage_cost_per_token = env::storage_byte_cost() *
-            self.tokens.extra_storage_in_bytes_per_token as Balance;
-        let storage_cost = storage_cost_per_token * n as Balance;
-
-        return mint_cost + storage_cost;
+        self.mint_cost_for(n) + self.storage_cost_for(n)
     }
 }
 
@@ -45,24 +70,63 @@ impl Contract {
     #[payable]
     pub fn buy(
         &mut self,
-        n: u64
+        n: u64,
+        referrer_id: Option<AccountId>,
+        promo_code: Option<String>,
+        points: Option<U128>,
     ) -> Vec<Token> {
-        require!(self.raffle.items_left() > 0, error::ERR_NO_ENOUGH_ITEMS);
+        require!(n > 0, error::ERR_ZERO_BOXES);
+        self.assert_not_paused(PauseTarget::Buy);
+        require!(
+            self.escrow_status() != escrow::EscrowStatus::Failed,
+            error::ERR_SALE_FAILED
+        );
+
+        let items_for_sale = self.items_for_sale();
+        require!(
+            items_for_sale > 0 && n <= items_for_sale,
+            error::ERR_NO_ENOUGH_ITEMS
+        );
 
         let init_storage_usage = env::storage_usage();
 
+        let phase = self.sale_phase();
         let buyer_id = env::signer_account_id();
         let deposit = env::attached_deposit();
-        self.assert_deposit(n, deposit);
 
-        let income = self.mint_cost_for(n);
-        self.total_income += income;
+        let mint_cost = self.redeem_promo_code(
+            promo_code,
+            &buyer_id,
+            self.mint_cost_for(n)
+        );
+        let mint_cost = self.spend_points(points, &buyer_id, mint_cost);
+        self.assert_deposit(n, mint_cost, deposit);
+
+        let price = self.unit_price();
+        self.last_sale_price = price;
+        let income = self.hold_for_rebate(&buyer_id, n, mint_cost);
+
+        // referral reward is paid out of income
+        // before it's distributed through royalties
+        let reward = self.reward_referrer(referrer_id, &buyer_id, n, income);
+        self.total_income += income - reward;
 
         // draw and mint tokens
         let tokens = self.mint_many(n, &buyer_id);
+        self.hold_in_escrow(&tokens, mint_cost, reward);
+        self.record_purchase(&buyer_id, &tokens, mint_cost, phase);
+
+        event::NearEvent::log_box_purchase(
+            buyer_id.to_string(),
+            n,
+            mint_cost.to_string(),
+            tokens.iter().map(|token| token.token_id.clone()).collect()
+        );
+        self.log_price_change(price);
+        self.update_phase();
 
         // refund extra storage deposit to buyer
-        let deposit_for_storage = deposit - self.mint_cost_for(n);
+        let deposit_for_storage = deposit - mint_cost;
         self.refund_storage_deposit(
             init_storage_usage,
             deposit_for_storage,
@@ -73,7 +137,7 @@ impl Contract {
     }
 
     /// deposit_for_storage = total_deposit - mint_price
-    fn refund_storage_deposit(
+    pub(crate) fn refund_storage_deposit(
         & self,
         init_storage_usage: u64,
         deposit_for_storage: Balance,
@@ -103,9 +167,10 @@ impl Contract {
     fn assert_deposit(
         &self,
         n: u64,
+        mint_cost: Balance,
         deposit: Balance
     ) {
-        let cost = self.cost_for(n);
+        let cost = mint_cost + self.storage_cost_for(n);
 
         require!(
             deposit >= cost,
@@ -124,11 +189,22 @@ impl Contract {
     pub fn distribute_income(
         &mut self
     ) {
+        self.assert_role(Role::Treasurer);
+        self.assert_not_paused(PauseTarget::DistributeIncome);
+        // income of a failed sale can be distributed,
+        // except for what is held for refunds
+        require!(
+            self.escrow_status() != escrow::EscrowStatus::Pending,
+            error::ERR_ESCROW_NOT_RELEASED
+        );
+
         if self.royalty.is_none() {
             return;
         }
 
-        let total_dist_amount = self.total_income - self.distributed_income;
+        let total_dist_amount = self.total_income
+            - self.distributed_income
+            - self.income_on_hold();
         if total_dist_amount <= 0 {
             return;
         }
@@ -141,10 +217,16 @@ impl Contract {
 
         self.distributed_income += total_dist_amount;
 
+        let mut distributed = vec![];
         for (account, amount) in payouts.payout.iter() {
             if *account != env::current_account_id() {
                 Promise::new(account.clone()).transfer(amount.0);
+                distributed.push(event::IncomeDistributedData {
+                    account_id: account.to_string(),
+                    amount: amount.0.to_string(),
+                });
             }
         }
+        event::NearEvent::log_income_distributed(distributed);
     }
 }
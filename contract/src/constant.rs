@@ -1,9 +1,24 @@
 use near_sdk::{
-    Balance, Gas,
+    Balance, Gas, StorageUsage,
 };
-use near_units::parse_gas;
+use near_units::{parse_gas, parse_near};
 
 pub const NO_DEPOSIT: Balance = 0;
 
 pub const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(parse_gas!("10 TGas") as u64);
 pub const GAS_FOR_NFT_TRANSFER_CALL: Gas = Gas(parse_gas!("30 TGas") as u64);
+pub const GAS_FOR_NFT_APPROVE: Gas = Gas(parse_gas!("10 TGas") as u64);
+pub const GAS_FOR_BID_REFUND: Gas = Gas(parse_gas!("5 TGas") as u64);
+pub const GAS_FOR_UPGRADE: Gas = Gas(parse_gas!("20 TGas") as u64);
+
+pub const DEFAULT_UNIT_PRICE: Balance = parse_near!("1 N");
+
+/// how long holders can refund after a sale failed, in nanoseconds
+pub const REFUND_PERIOD: u64 = 30 * 24 * 3600 * 1_000_000_000;
+
+/// storage for bookkeeping of a purchase besides the tokens,
+/// e.g. purchase record, rebate and referral records
+pub const STORAGE_PER_PURCHASE: StorageUsage = 500;
+/// storage for bookkeeping of each token bought,
+/// e.g. its id in the purchase record, purchase and escrow lookups
+pub const STORAGE_PER_PURCHASED_TOKEN: StorageUsage = 250;
@@ -8,3 +8,69 @@ pub const ERR_TOKEN_NOT_EXIST: &str = "E04: token not exist.";
 pub const ERR_BAD_ROYALTY_VALUE: &str = "E05: bad royalty value.";
 pub const ERR_TOO_MANY_ROYALTY_ACCOUNT: &str = "E06: too many royalty accounts.";
 pub const ERR_BAD_ROYALTY_RATE: &str = "E07: bad royalty rate.";
+
+pub const ERR_BAD_PRICE_SCHEDULE: &str = "E08: bad price schedule.";
+pub const ERR_AUCTION_NOT_CLEARED: &str = "E09: auction not cleared yet.";
+pub const ERR_NO_REBATE: &str = "E10: no rebate to claim.";
+pub const ERR_BAD_DISCOUNT_TIER: &str = "E11: bad discount tier.";
+
+pub const ERR_BAD_REFERRAL_CONFIG: &str = "E12: bad referral config.";
+pub const ERR_SELF_REFERRAL: &str = "E13: can not refer yourself.";
+pub const ERR_NO_REFERRAL_REWARD: &str = "E14: no referral reward to claim.";
+
+pub const ERR_NOT_OWNER: &str = "E15: only owner can call this method.";
+
+pub const ERR_PROMO_CODE_INVALID: &str = "E16: invalid promo code.";
+pub const ERR_PROMO_CODE_EXPIRED: &str = "E17: promo code expired.";
+pub const ERR_PROMO_CODE_USED_UP: &str = "E18: promo code used up.";
+pub const ERR_PROMO_CODE_BAD_DISCOUNT: &str = "E19: bad promo code discount.";
+
+pub const ERR_NO_ENOUGH_RESERVED: &str = "E20: no enough reserved items.";
+pub const ERR_ZERO_BOXES: &str = "E21: must buy at least one box.";
+pub const ERR_ITEM_NOT_AVAILABLE: &str = "E22: item not available.";
+
+pub const ERR_MISSING_ROLE: &str = "E23: missing role.";
+pub const ERR_BAD_ROLE: &str = "E24: bad role.";
+pub const ERR_NOT_PENDING_OWNER: &str = "E25: not pending owner.";
+
+pub const ERR_BUY_PAUSED: &str = "E26: buy is paused.";
+pub const ERR_TRANSFER_PAUSED: &str = "E27: transfer is paused.";
+pub const ERR_DISTRIBUTE_INCOME_PAUSED: &str = "E28: income distribution is paused.";
+pub const ERR_APPROVAL_PAUSED: &str = "E29: approval is paused.";
+
+pub const ERR_NO_STATE: &str = "E30: contract state not found.";
+pub const ERR_NO_CODE: &str = "E31: no contract code attached.";
+pub const ERR_NO_ENOUGH_GAS: &str = "E32: no enough gas.";
+
+pub const ERR_ESCROW_NOT_RELEASED: &str = "E33: escrow not released.";
+pub const ERR_SALE_FAILED: &str = "E34: sale failed.";
+pub const ERR_ESCROW_NOT_PENDING: &str = "E35: escrow not pending.";
+pub const ERR_SALE_NOT_FAILED: &str = "E36: sale not failed.";
+pub const ERR_NOT_TOKEN_OWNER: &str = "E37: not token owner.";
+pub const ERR_NO_REFUND: &str = "E38: no refund for token.";
+pub const ERR_BAD_ESCROW_CONFIG: &str = "E39: bad escrow config.";
+pub const ERR_REFUND_CLOSED: &str = "E40: refund period is over.";
+pub const ERR_EMPTY_BATCH: &str = "E41: empty batch.";
+pub const ERR_TOKEN_LOCKED: &str = "E42: token is locked.";
+pub const ERR_SOULBOUND: &str = "E43: token is soulbound.";
+pub const ERR_NOT_LISTED: &str = "E44: token not listed.";
+pub const ERR_BAD_LISTING_PRICE: &str = "E45: bad listing price.";
+pub const ERR_BUY_OWN_LISTING: &str = "E46: can't buy own listing.";
+pub const ERR_NO_AUCTION: &str = "E47: auction not found.";
+pub const ERR_AUCTION_NOT_ACTIVE: &str = "E48: auction not active.";
+pub const ERR_BID_TOO_LOW: &str = "E49: bid too low.";
+pub const ERR_AUCTION_NOT_ENDED: &str = "E50: auction not ended.";
+pub const ERR_BAD_AUCTION_CONFIG: &str = "E51: bad auction config.";
+pub const ERR_NO_BID_REFUND: &str = "E52: no bid refund.";
+pub const ERR_ITEM_NOT_DRAWN: &str = "E53: item not drawn.";
+pub const ERR_STAKING_DISABLED: &str = "E54: staking is disabled.";
+pub const ERR_NOT_STAKED: &str = "E55: token not staked.";
+pub const ERR_BAD_STAKING_CONFIG: &str = "E56: bad staking config.";
+pub const ERR_NO_ENOUGH_POINTS: &str = "E57: no enough points.";
+pub const ERR_TOKEN_STAKED: &str = "E58: token is staked.";
+pub const ERR_NO_FUSION_POOL: &str = "E59: fusion pool not set.";
+pub const ERR_FUSION_POOL_EXISTS: &str = "E60: fusion pool already set.";
+pub const ERR_NO_RECIPE: &str = "E61: recipe not found.";
+pub const ERR_BAD_RECIPE: &str = "E62: bad recipe.";
+pub const ERR_RECIPE_NOT_MATCHED: &str = "E63: tokens don't match the recipe.";
+pub const ERR_REROLL_DISABLED: &str = "E64: reroll is disabled.";
@@ -0,0 +1,493 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    serde::{Serialize, Deserialize},
+    json_types::{U128, U64},
+    near_bindgen, env, require, assert_one_yocto, Balance, Promise,
+};
+
+/// --- Escrow
+/// Optionally, primary income is held until `threshold` boxes are
+/// sold. If that doesn't happen before `deadline`, or the sale is
+/// cancelled, the sale fails and holders can burn their tokens
+/// for a full refund of what was paid for them, until
+/// `REFUND_PERIOD` after the failure. Income that isn't held
+/// for refunds can be distributed once the sale failed,
+/// and all of it after the refund period.
+/// Referral rewards of a failed sale can't be claimed, so the part
+/// of a refund that was set aside as reward is not taken from income.
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EscrowConfig {
+    pub threshold: u64,
+    pub deadline: U64,
+}
+
+impl EscrowConfig {
+    /// the threshold must be reachable by public sale
+    /// before the deadline
+    pub fn assert_valid(
+        &self,
+        items_for_sale: u64
+    ) {
+        require!(
+            self.threshold > 0 && self.threshold <= items_for_sale,
+            error::ERR_BAD_ESCROW_CONFIG
+        );
+        require!(
+            self.deadline.0 > env::block_timestamp(),
+            error::ERR_BAD_ESCROW_CONFIG
+        );
+    }
+}
+
+/// what was paid for a token, and the referral reward taken out of it
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Debug)]
+pub struct EscrowPayment {
+    paid: Balance,
+    reward: Balance,
+}
+
+impl EscrowPayment {
+    /// the part of the payment counted as income
+    fn income(&self) -> Balance {
+        self.paid - self.reward
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Escrow {
+    config: EscrowConfig,
+    sold: u64,
+    cancelled_at: Option<u64>,
+    /// income of the payments that can still be refunded
+    held: Balance,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum EscrowStatus {
+    Pending,
+    Released,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EscrowView {
+    pub threshold: u64,
+    pub deadline: U64,
+    pub sold: u64,
+    pub status: EscrowStatus,
+}
+
+impl Escrow {
+    pub fn new(
+        config: EscrowConfig
+    ) -> Self {
+        Self {
+            config,
+            sold: 0,
+            cancelled_at: None,
+            held: 0,
+        }
+    }
+
+    fn status(
+        &self
+    ) -> EscrowStatus {
+        if self.cancelled_at.is_some() {
+            EscrowStatus::Failed
+        } else if self.sold >= self.config.threshold {
+            EscrowStatus::Released
+        } else if env::block_timestamp() >= self.config.deadline.0 {
+            EscrowStatus::Failed
+        } else {
+            EscrowStatus::Pending
+        }
+    }
+
+    /// refunds are open for `REFUND_PERIOD` after the sale failed
+    fn refund_open(
+        &self
+    ) -> bool {
+        if self.status() != EscrowStatus::Failed {
+            return false;
+        }
+
+        let failed_at = self.cancelled_at.unwrap_or(self.config.deadline.0);
+        env::block_timestamp() < failed_at.saturating_add(REFUND_PERIOD)
+    }
+}
+
+impl Contract {
+    /// released if there is no escrow
+    pub(crate) fn escrow_status(
+        &self
+    ) -> EscrowStatus {
+        self.escrow.as_ref()
+            .map_or(EscrowStatus::Released, |escrow| escrow.status())
+    }
+
+    /// income that can't be distributed yet
+    pub(crate) fn income_on_hold(
+        &self
+    ) -> Balance {
+        match self.escrow.as_ref() {
+            Some(escrow) if escrow.refund_open() => escrow.held,
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn assert_escrow_released(
+        &self
+    ) {
+        require!(
+            self.escrow_status() == EscrowStatus::Released,
+            error::ERR_ESCROW_NOT_RELEASED
+        );
+    }
+
+    /// record what was paid for each token while escrow is pending,
+    /// and the referral reward out of it,
+    /// the first token takes the remainder
+    pub(crate) fn hold_in_escrow(
+        &mut self,
+        tokens: &[Token],
+        paid: Balance,
+        reward: Balance
+    ) {
+        if self.escrow_status() != EscrowStatus::Pending || tokens.is_empty() {
+            return;
+        }
+
+        let n = tokens.len() as u64;
+        let paid_shares = split(paid, n);
+        let reward_shares = split(reward, n);
+
+        for (i, token) in tokens.iter().enumerate() {
+            self.paid_by_token.insert(&token.token_id, &EscrowPayment {
+                paid: paid_shares[i],
+                reward: reward_shares[i],
+            });
+        }
+
+        let escrow = self.escrow.as_mut().unwrap();
+        escrow.sold += n;
+        escrow.held += paid - reward;
+    }
+
+    /// remove the payment recorded for a token,
+    /// so its income is no longer held for a refund
+    pub(crate) fn take_escrow_payment(
+        &mut self,
+        token_id: &TokenId
+    ) -> Option<EscrowPayment> {
+        let payment = self.paid_by_token.remove(token_id)?;
+        if let Some(escrow) = self.escrow.as_mut() {
+            escrow.held -= payment.income();
+        }
+
+        Some(payment)
+    }
+
+    /// record a payment taken from another token,
+    /// so its income is held for a refund again
+    pub(crate) fn put_escrow_payment(
+        &mut self,
+        token_id: &TokenId,
+        payment: EscrowPayment
+    ) {
+        self.paid_by_token.insert(token_id, &payment);
+        if let Some(escrow) = self.escrow.as_mut() {
+            escrow.held += payment.income();
+        }
+    }
+}
+
+/// split `amount` into `n` shares, the first one takes the remainder
+fn split(
+    amount: Balance,
+    n: u64
+) -> Vec<Balance> {
+    let share = amount / Balance::from(n);
+    let remainder = amount - share * Balance::from(n);
+
+    (0..n)
+        .map(|i| if i == 0 { share + remainder } else { share })
+        .collect()
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn escrow(
+        &self
+    ) -> Option<EscrowView> {
+        self.escrow.as_ref().map(|escrow| EscrowView {
+            threshold: escrow.config.threshold,
+            deadline: escrow.config.deadline,
+            sold: escrow.sold,
+            status: escrow.status(),
+        })
+    }
+
+    /// amount refundable for a token if the sale fails
+    pub fn refund_of(
+        &self,
+        token_id: TokenId
+    ) -> Option<U128> {
+        self.paid_by_token.get(&token_id)
+            .map(|payment| U128(payment.paid))
+    }
+
+    /// cancel a pending sale, so holders can get refunds
+    #[payable]
+    pub fn cancel_sale(
+        &mut self
+    ) {
+        assert_one_yocto();
+        self.assert_role(Role::Admin);
+        require!(
+            self.escrow_status() == EscrowStatus::Pending,
+            error::ERR_ESCROW_NOT_PENDING
+        );
+
+        self.escrow.as_mut().unwrap().cancelled_at = Some(env::block_timestamp());
+        self.update_phase();
+    }
+
+    /// burn a token of a failed sale for a full refund
+    #[payable]
+    pub fn refund(
+        &mut self,
+        token_id: TokenId
+    ) -> U128 {
+        assert_one_yocto();
+        require!(
+            self.escrow_status() == EscrowStatus::Failed,
+            error::ERR_SALE_NOT_FAILED
+        );
+        require!(
+            self.escrow.as_ref().unwrap().refund_open(),
+            error::ERR_REFUND_CLOSED
+        );
+
+        let owner_id = self.owner_of(&token_id)
+            .expect(error::ERR_TOKEN_NOT_EXIST);
+        require!(
+            owner_id == env::predecessor_account_id(),
+            error::ERR_NOT_TOKEN_OWNER
+        );
+
+        let payment = self.take_escrow_payment(&token_id)
+            .expect(error::ERR_NO_REFUND);
+
+        self.internal_burn(&token_id, &owner_id);
+        event::NearEvent::log_nft_burn(
+            owner_id.to_string(),
+            vec![token_id],
+            Some("refund".to_string()),
+            None
+        );
+
+        // the reward was never counted as income
+        self.total_income -= payment.income();
+        Promise::new(owner_id).transfer(payment.paid);
+
+        payment.paid.into()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{context, setup, set_predecessor};
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+
+    /// a call from accounts(1) with 1 yocto attached
+    fn set_time(timestamp: u64) {
+        testing_env!(context(accounts(1))
+            .attached_deposit(1)
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    fn escrow() -> Escrow {
+        Escrow::new(EscrowConfig {
+            threshold: 5,
+            deadline: U64(1000),
+        })
+    }
+
+    #[test]
+    fn released_when_threshold_reached() {
+        set_time(0);
+        let mut escrow = escrow();
+        assert_eq!(EscrowStatus::Pending, escrow.status());
+
+        escrow.sold = 5;
+        assert_eq!(EscrowStatus::Released, escrow.status());
+
+        // stays released after deadline
+        set_time(1000);
+        assert_eq!(EscrowStatus::Released, escrow.status());
+    }
+
+    #[test]
+    fn failed_after_deadline() {
+        set_time(999);
+        let mut escrow = escrow();
+        escrow.sold = 4;
+        assert_eq!(EscrowStatus::Pending, escrow.status());
+
+        set_time(1000);
+        assert_eq!(EscrowStatus::Failed, escrow.status());
+    }
+
+    #[test]
+    fn failed_when_cancelled() {
+        set_time(0);
+        let mut escrow = escrow();
+        escrow.cancelled_at = Some(0);
+        assert_eq!(EscrowStatus::Failed, escrow.status());
+    }
+
+    /// bob bought 2 boxes for 1_001 while escrow is pending,
+    /// 101 of which went to a referrer
+    fn setup_sale() -> (Contract, Vec<TokenId>) {
+        let mut contract = setup();
+        contract.escrow = Some(escrow());
+
+        set_predecessor(accounts(1));
+        let tokens = contract.mint_many(2, &accounts(1));
+        contract.total_income += 1_001 - 101;
+        contract.hold_in_escrow(&tokens, 1_001, 101);
+
+        let token_ids = tokens.into_iter().map(|token| token.token_id).collect();
+        (contract, token_ids)
+    }
+
+    #[test]
+    fn split_shares() {
+        assert_eq!(vec![4, 3, 3], split(10, 3));
+        assert_eq!(vec![0, 0], split(0, 2));
+    }
+
+    #[test]
+    fn hold_payments() {
+        let (contract, token_ids) = setup_sale();
+        assert_eq!(2, contract.escrow().unwrap().sold);
+        assert_eq!(Some(U128(501)), contract.refund_of(token_ids[0].clone()));
+        assert_eq!(Some(U128(500)), contract.refund_of(token_ids[1].clone()));
+        assert_eq!(
+            Some(EscrowPayment { paid: 501, reward: 51 }),
+            contract.paid_by_token.get(&token_ids[0])
+        );
+        assert_eq!(900, contract.escrow.as_ref().unwrap().held);
+    }
+
+    #[test]
+    fn cancel_and_refund() {
+        let (mut contract, token_ids) = setup_sale();
+
+        set_predecessor(accounts(0));
+        contract.cancel_sale();
+        assert_eq!(EscrowStatus::Failed, contract.escrow().unwrap().status);
+        assert!(get_logs().last().unwrap().contains(r#""event":"phase_changed""#));
+        assert_eq!(900, contract.income_on_hold());
+
+        set_predecessor(accounts(1));
+        assert_eq!(U128(501), contract.refund(token_ids[0].clone()));
+        assert!(contract.owner_of(&token_ids[0]).is_none());
+        assert_eq!(900 - 450, contract.total_income);
+        assert_eq!(450, contract.income_on_hold());
+
+        assert_eq!(U128(500), contract.refund(token_ids[1].clone()));
+        assert_eq!(0, contract.total_income);
+        assert_eq!(0, contract.income_on_hold());
+        assert!(contract.refund_of(token_ids[1].clone()).is_none());
+    }
+
+    #[test]
+    fn burn_releases_held_income() {
+        let (mut contract, token_ids) = setup_sale();
+
+        set_predecessor(accounts(0));
+        contract.cancel_sale();
+
+        set_predecessor(accounts(1));
+        contract.nft_burn(token_ids[0].clone());
+        assert!(contract.refund_of(token_ids[0].clone()).is_none());
+        assert!(contract.purchase_of_token(token_ids[0].clone()).is_none());
+        assert_eq!(450, contract.income_on_hold());
+        assert_eq!(900, contract.total_income);
+    }
+
+    #[test]
+    fn release_income_after_refund_period() {
+        let (mut contract, token_ids) = setup_sale();
+
+        // failed at the deadline
+        set_time(1000);
+        assert_eq!(900, contract.income_on_hold());
+
+        set_time(1000 + REFUND_PERIOD - 1);
+        assert_eq!(U128(501), contract.refund(token_ids[0].clone()));
+        assert_eq!(450, contract.income_on_hold());
+
+        // unclaimed refunds are no longer held
+        set_time(1000 + REFUND_PERIOD);
+        assert_eq!(0, contract.income_on_hold());
+        assert_eq!(450, contract.total_income);
+    }
+
+    #[test]
+    #[should_panic(expected = "E40: refund period is over.")]
+    fn refund_after_refund_period() {
+        let (mut contract, token_ids) = setup_sale();
+        set_time(1000 + REFUND_PERIOD);
+        contract.refund(token_ids[0].clone());
+    }
+
+    #[test]
+    #[should_panic(expected = "E36: sale not failed.")]
+    fn refund_before_failed() {
+        let (mut contract, token_ids) = setup_sale();
+        set_predecessor(accounts(1));
+        contract.refund(token_ids[0].clone());
+    }
+
+    #[test]
+    #[should_panic(expected = "E35: escrow not pending.")]
+    fn cancel_twice() {
+        let (mut contract, _) = setup_sale();
+        set_predecessor(accounts(0));
+        contract.cancel_sale();
+        contract.cancel_sale();
+    }
+
+    #[test]
+    #[should_panic(expected = "E39: bad escrow config.")]
+    fn threshold_above_supply() {
+        set_time(0);
+        EscrowConfig { threshold: 11, deadline: U64(1000) }.assert_valid(10);
+    }
+
+    #[test]
+    #[should_panic(expected = "E39: bad escrow config.")]
+    fn zero_threshold() {
+        set_time(0);
+        EscrowConfig { threshold: 0, deadline: U64(1000) }.assert_valid(10);
+    }
+
+    #[test]
+    #[should_panic(expected = "E39: bad escrow config.")]
+    fn deadline_passed() {
+        set_time(1000);
+        EscrowConfig { threshold: 5, deadline: U64(1000) }.assert_valid(10);
+    }
+}
@@ -9,6 +9,7 @@ use serde_with::skip_serializing_none;
 #[serde(rename_all = "snake_case")]
 pub enum NearEvent {
     Nep171(Nep171Event),
+    MysteryBox(MysteryBoxEvent),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -26,6 +27,7 @@ pub enum Nep171EventKind {
     NftMint(Vec<NftMintData>),
     NftTransfer(Vec<NftTransferData>),
     NftBurn(Vec<NftBurnData>),
+    ContractMetadataUpdate(Vec<ContractMetadataUpdateData>),
 }
 
 #[skip_serializing_none]
@@ -55,6 +57,181 @@ pub struct NftBurnData {
     pub memo: Option<String>,
 }
 
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContractMetadataUpdateData {
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MysteryBoxEvent {
+    pub version: String,
+    #[serde(flatten)]
+    pub event_kind: MysteryBoxEventKind,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+pub enum MysteryBoxEventKind {
+    PromoCodeRedeem(Vec<PromoCodeRedeemData>),
+    RoleGrant(Vec<RoleData>),
+    RoleRevoke(Vec<RoleData>),
+    OwnershipTransferStart(Vec<OwnershipTransferData>),
+    OwnershipTransfer(Vec<OwnershipTransferData>),
+    BoxPurchase(Vec<BoxPurchaseData>),
+    IncomeDistributed(Vec<IncomeDistributedData>),
+    PriceChanged(Vec<PriceChangedData>),
+    PhaseChanged(Vec<PhaseChangedData>),
+    Reveal(Vec<RevealData>),
+    NftApprove(Vec<NftApproveData>),
+    NftRevoke(Vec<NftRevokeData>),
+    NftRevokeAll(Vec<NftRevokeAllData>),
+    ListingCreate(Vec<ListingData>),
+    ListingRemove(Vec<ListingRemoveData>),
+    ListingSold(Vec<ListingSoldData>),
+    AuctionCreate(Vec<AuctionCreateData>),
+    AuctionBid(Vec<AuctionBidData>),
+    AuctionSettle(Vec<AuctionSettleData>),
+    Stake(Vec<StakeData>),
+    Unstake(Vec<StakeData>),
+    PointsSpend(Vec<PointsSpendData>),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PromoCodeRedeemData {
+    pub account_id: String,
+    pub code_hash: String,
+    pub discount: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RoleData {
+    pub account_id: String,
+    pub role: String,
+    pub authorized_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OwnershipTransferData {
+    pub old_owner_id: String,
+    pub new_owner_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BoxPurchaseData {
+    pub buyer_id: String,
+    pub n: u64,
+    pub price: String,
+    pub token_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IncomeDistributedData {
+    pub account_id: String,
+    pub amount: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PriceChangedData {
+    pub old_price: String,
+    pub new_price: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PhaseChangedData {
+    pub old_phase: String,
+    pub new_phase: String,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RevealData {
+    pub owner_id: String,
+    pub token_id: String,
+    pub media: Option<String>,
+    pub reference: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftApproveData {
+    pub owner_id: String,
+    pub token_id: String,
+    pub account_id: String,
+    pub approval_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftRevokeData {
+    pub owner_id: String,
+    pub token_id: String,
+    pub account_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftRevokeAllData {
+    pub owner_id: String,
+    pub token_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListingData {
+    pub owner_id: String,
+    pub token_id: String,
+    pub price: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListingRemoveData {
+    pub owner_id: String,
+    pub token_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListingSoldData {
+    pub seller_id: String,
+    pub buyer_id: String,
+    pub token_id: String,
+    pub price: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuctionCreateData {
+    pub index: u64,
+    pub reserve_price: String,
+    pub start_at: String,
+    pub end_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuctionBidData {
+    pub index: u64,
+    pub bidder_id: String,
+    pub amount: String,
+    pub end_at: String,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuctionSettleData {
+    pub index: u64,
+    pub winner_id: Option<String>,
+    pub amount: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StakeData {
+    pub owner_id: String,
+    pub token_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PointsSpendData {
+    pub account_id: String,
+    pub points: String,
+    pub discount: String,
+}
+
 impl Display for NearEvent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(&format!("EVENT_JSON:{}", self.to_json_string()))
@@ -71,6 +248,11 @@ impl NearEvent {
         NearEvent::new_171("1.0.0".to_string(), event_kind)
     }
 
+    /// contract_metadata_update is only defined since nep171 1.1.0
+    pub fn new_171_v1_1(event_kind: Nep171EventKind) -> Self {
+        NearEvent::new_171("1.1.0".to_string(), event_kind)
+    }
+
     pub fn nft_burn(data: Vec<NftBurnData>) -> Self {
         NearEvent::new_171_v1(Nep171EventKind::NftBurn(data))
     }
@@ -82,6 +264,106 @@ impl NearEvent {
         NearEvent::new_171_v1(Nep171EventKind::NftMint(data))
     }
 
+    pub fn contract_metadata_update(data: Vec<ContractMetadataUpdateData>) -> Self {
+        NearEvent::new_171_v1_1(Nep171EventKind::ContractMetadataUpdate(data))
+    }
+
+    pub fn new_mystery_box(version: String, event_kind: MysteryBoxEventKind) -> Self {
+        NearEvent::MysteryBox(MysteryBoxEvent { version, event_kind })
+    }
+
+    pub fn new_mystery_box_v1(event_kind: MysteryBoxEventKind) -> Self {
+        NearEvent::new_mystery_box("1.0.0".to_string(), event_kind)
+    }
+
+    pub fn promo_code_redeem(data: Vec<PromoCodeRedeemData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::PromoCodeRedeem(data))
+    }
+
+    pub fn role_grant(data: Vec<RoleData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::RoleGrant(data))
+    }
+
+    pub fn role_revoke(data: Vec<RoleData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::RoleRevoke(data))
+    }
+
+    pub fn ownership_transfer_start(data: Vec<OwnershipTransferData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::OwnershipTransferStart(data))
+    }
+
+    pub fn ownership_transfer(data: Vec<OwnershipTransferData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::OwnershipTransfer(data))
+    }
+
+    pub fn box_purchase(data: Vec<BoxPurchaseData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::BoxPurchase(data))
+    }
+
+    pub fn income_distributed(data: Vec<IncomeDistributedData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::IncomeDistributed(data))
+    }
+
+    pub fn price_changed(data: Vec<PriceChangedData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::PriceChanged(data))
+    }
+
+    pub fn phase_changed(data: Vec<PhaseChangedData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::PhaseChanged(data))
+    }
+
+    pub fn reveal(data: Vec<RevealData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::Reveal(data))
+    }
+
+    pub fn nft_approve(data: Vec<NftApproveData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::NftApprove(data))
+    }
+
+    pub fn nft_revoke(data: Vec<NftRevokeData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::NftRevoke(data))
+    }
+
+    pub fn nft_revoke_all(data: Vec<NftRevokeAllData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::NftRevokeAll(data))
+    }
+
+    pub fn listing_create(data: Vec<ListingData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::ListingCreate(data))
+    }
+
+    pub fn listing_remove(data: Vec<ListingRemoveData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::ListingRemove(data))
+    }
+
+    pub fn listing_sold(data: Vec<ListingSoldData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::ListingSold(data))
+    }
+
+    pub fn auction_create(data: Vec<AuctionCreateData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::AuctionCreate(data))
+    }
+
+    pub fn auction_bid(data: Vec<AuctionBidData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::AuctionBid(data))
+    }
+
+    pub fn auction_settle(data: Vec<AuctionSettleData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::AuctionSettle(data))
+    }
+
+    pub fn stake(data: Vec<StakeData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::Stake(data))
+    }
+
+    pub fn unstake(data: Vec<StakeData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::Unstake(data))
+    }
+
+    pub fn points_spend(data: Vec<PointsSpendData>) -> Self {
+        NearEvent::new_mystery_box_v1(MysteryBoxEventKind::PointsSpend(data))
+    }
+
     pub(crate) fn to_json_string(&self) -> String {
         serde_json::to_string(self).unwrap()
     }
@@ -130,6 +412,108 @@ impl NearEvent {
     pub fn log_nft_burns(data: Vec<NftBurnData>) {
         NearEvent::nft_burn(data).log();
     }
+
+    pub fn log_contract_metadata_update(memo: Option<String>) {
+        NearEvent::contract_metadata_update(vec![ContractMetadataUpdateData { memo }]).log();
+    }
+
+    pub fn log_role_grant(account_id: String, role: String, authorized_id: String) {
+        NearEvent::role_grant(vec![RoleData { account_id, role, authorized_id }]).log();
+    }
+
+    pub fn log_role_revoke(account_id: String, role: String, authorized_id: String) {
+        NearEvent::role_revoke(vec![RoleData { account_id, role, authorized_id }]).log();
+    }
+
+    pub fn log_ownership_transfer_start(old_owner_id: String, new_owner_id: String) {
+        NearEvent::ownership_transfer_start(vec![OwnershipTransferData { old_owner_id, new_owner_id }]).log();
+    }
+
+    pub fn log_ownership_transfer(old_owner_id: String, new_owner_id: String) {
+        NearEvent::ownership_transfer(vec![OwnershipTransferData { old_owner_id, new_owner_id }]).log();
+    }
+
+    pub fn log_promo_code_redeem(account_id: String, code_hash: String, discount: String) {
+        NearEvent::promo_code_redeem(vec![PromoCodeRedeemData { account_id, code_hash, discount }]).log();
+    }
+
+    pub fn log_box_purchase(buyer_id: String, n: u64, price: String, token_ids: Vec<String>) {
+        NearEvent::box_purchase(vec![BoxPurchaseData { buyer_id, n, price, token_ids }]).log();
+    }
+
+    pub fn log_income_distributed(data: Vec<IncomeDistributedData>) {
+        NearEvent::income_distributed(data).log();
+    }
+
+    pub fn log_price_changed(old_price: String, new_price: String) {
+        NearEvent::price_changed(vec![PriceChangedData { old_price, new_price }]).log();
+    }
+
+    pub fn log_phase_changed(old_phase: String, new_phase: String) {
+        NearEvent::phase_changed(vec![PhaseChangedData { old_phase, new_phase }]).log();
+    }
+
+    pub fn log_reveals(data: Vec<RevealData>) {
+        NearEvent::reveal(data).log();
+    }
+
+    pub fn log_nft_approve(owner_id: String, token_id: String, account_id: String, approval_id: u64) {
+        NearEvent::nft_approve(vec![NftApproveData { owner_id, token_id, account_id, approval_id }]).log();
+    }
+
+    pub fn log_nft_revoke(owner_id: String, token_id: String, account_id: String) {
+        NearEvent::nft_revoke(vec![NftRevokeData { owner_id, token_id, account_id }]).log();
+    }
+
+    pub fn log_nft_revoke_all(owner_id: String, token_id: String) {
+        NearEvent::nft_revoke_all(vec![NftRevokeAllData { owner_id, token_id }]).log();
+    }
+
+    pub fn log_listing_create(owner_id: String, token_id: String, price: String) {
+        NearEvent::listing_create(vec![ListingData { owner_id, token_id, price }]).log();
+    }
+
+    pub fn log_listing_remove(owner_id: String, token_id: String) {
+        NearEvent::listing_remove(vec![ListingRemoveData { owner_id, token_id }]).log();
+    }
+
+    pub fn log_listing_sold(seller_id: String, buyer_id: String, token_id: String, price: String) {
+        NearEvent::listing_sold(vec![ListingSoldData { seller_id, buyer_id, token_id, price }]).log();
+    }
+
+    pub fn log_auction_create(index: u64, reserve_price: String, start_at: u64, end_at: u64) {
+        NearEvent::auction_create(vec![AuctionCreateData {
+            index,
+            reserve_price,
+            start_at: start_at.to_string(),
+            end_at: end_at.to_string(),
+        }]).log();
+    }
+
+    pub fn log_auction_bid(index: u64, bidder_id: String, amount: String, end_at: u64) {
+        NearEvent::auction_bid(vec![AuctionBidData {
+            index,
+            bidder_id,
+            amount,
+            end_at: end_at.to_string(),
+        }]).log();
+    }
+
+    pub fn log_auction_settle(index: u64, winner_id: Option<String>, amount: String) {
+        NearEvent::auction_settle(vec![AuctionSettleData { index, winner_id, amount }]).log();
+    }
+
+    pub fn log_stake(owner_id: String, token_ids: Vec<String>) {
+        NearEvent::stake(vec![StakeData { owner_id, token_ids }]).log();
+    }
+
+    pub fn log_unstake(owner_id: String, token_ids: Vec<String>) {
+        NearEvent::unstake(vec![StakeData { owner_id, token_ids }]).log();
+    }
+
+    pub fn log_points_spend(account_id: String, points: String, discount: String) {
+        NearEvent::points_spend(vec![PointsSpendData { account_id, points, discount }]).log();
+    }
 }
 
 #[cfg(test)]
@@ -254,4 +638,330 @@ mod tests {
             r#"{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"authorized_id":"4","old_owner_id":"alice","new_owner_id":"bob","token_ids":["2","3"],"memo":"has memo"},{"old_owner_id":"bob","new_owner_id":"alice","token_ids":["0","1"]}]}"#
         );
     }
+
+    #[test]
+    fn contract_metadata_update() {
+        let log = NearEvent::contract_metadata_update(vec![ContractMetadataUpdateData {
+            memo: None,
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nep171","version":"1.1.0","event":"contract_metadata_update","data":[{}]}"#
+        );
+
+        let log = NearEvent::contract_metadata_update(vec![ContractMetadataUpdateData {
+            memo: Some("new icon".to_string()),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nep171","version":"1.1.0","event":"contract_metadata_update","data":[{"memo":"new icon"}]}"#
+        );
+    }
+
+    #[test]
+    fn promo_code_redeem() {
+        let log = NearEvent::promo_code_redeem(vec![PromoCodeRedeemData {
+            account_id: "bob".to_string(),
+            code_hash: "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi".to_string(),
+            discount: "100".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"promo_code_redeem","data":[{"account_id":"bob","code_hash":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi","discount":"100"}]}"#
+        );
+    }
+
+    #[test]
+    fn role_grant() {
+        let log = NearEvent::role_grant(vec![RoleData {
+            account_id: "bob".to_string(),
+            role: "minter".to_string(),
+            authorized_id: "alice".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"role_grant","data":[{"account_id":"bob","role":"minter","authorized_id":"alice"}]}"#
+        );
+    }
+
+    #[test]
+    fn role_revoke() {
+        let log = NearEvent::role_revoke(vec![RoleData {
+            account_id: "bob".to_string(),
+            role: "admin".to_string(),
+            authorized_id: "alice".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"role_revoke","data":[{"account_id":"bob","role":"admin","authorized_id":"alice"}]}"#
+        );
+    }
+
+    #[test]
+    fn ownership_transfer() {
+        let start_log = NearEvent::ownership_transfer_start(vec![OwnershipTransferData {
+            old_owner_id: "alice".to_string(),
+            new_owner_id: "bob".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            start_log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"ownership_transfer_start","data":[{"old_owner_id":"alice","new_owner_id":"bob"}]}"#
+        );
+
+        let log = NearEvent::ownership_transfer(vec![OwnershipTransferData {
+            old_owner_id: "alice".to_string(),
+            new_owner_id: "bob".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"ownership_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob"}]}"#
+        );
+    }
+
+    #[test]
+    fn box_purchase() {
+        let log = NearEvent::box_purchase(vec![BoxPurchaseData {
+            buyer_id: "bob".to_string(),
+            n: 2,
+            price: "2000".to_string(),
+            token_ids: make_tokens(vec!["3", "7"]),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"box_purchase","data":[{"buyer_id":"bob","n":2,"price":"2000","token_ids":["3","7"]}]}"#
+        );
+    }
+
+    #[test]
+    fn income_distributed() {
+        let log = NearEvent::income_distributed(vec![
+            IncomeDistributedData {
+                account_id: "bob".to_string(),
+                amount: "700".to_string(),
+            },
+            IncomeDistributedData {
+                account_id: "alice".to_string(),
+                amount: "300".to_string(),
+            },
+        ])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"income_distributed","data":[{"account_id":"bob","amount":"700"},{"account_id":"alice","amount":"300"}]}"#
+        );
+    }
+
+    #[test]
+    fn price_changed() {
+        let log = NearEvent::price_changed(vec![PriceChangedData {
+            old_price: "1000".to_string(),
+            new_price: "1100".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"price_changed","data":[{"old_price":"1000","new_price":"1100"}]}"#
+        );
+    }
+
+    #[test]
+    fn phase_changed() {
+        let log = NearEvent::phase_changed(vec![PhaseChangedData {
+            old_phase: "open".to_string(),
+            new_phase: "sold_out".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"phase_changed","data":[{"old_phase":"open","new_phase":"sold_out"}]}"#
+        );
+    }
+
+    #[test]
+    fn reveal() {
+        let log = NearEvent::reveal(vec![
+            RevealData {
+                owner_id: "bob".to_string(),
+                token_id: "3".to_string(),
+                media: Some("a.png".to_string()),
+                reference: Some("a.json".to_string()),
+            },
+            RevealData {
+                owner_id: "bob".to_string(),
+                token_id: "7".to_string(),
+                media: None,
+                reference: None,
+            },
+        ])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"reveal","data":[{"owner_id":"bob","token_id":"3","media":"a.png","reference":"a.json"},{"owner_id":"bob","token_id":"7"}]}"#
+        );
+    }
+
+    #[test]
+    fn nft_approve() {
+        let log = NearEvent::nft_approve(vec![NftApproveData {
+            owner_id: "bob".to_string(),
+            token_id: "3".to_string(),
+            account_id: "market".to_string(),
+            approval_id: 1,
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"nft_approve","data":[{"owner_id":"bob","token_id":"3","account_id":"market","approval_id":1}]}"#
+        );
+    }
+
+    #[test]
+    fn nft_revoke() {
+        let log = NearEvent::nft_revoke(vec![NftRevokeData {
+            owner_id: "bob".to_string(),
+            token_id: "3".to_string(),
+            account_id: "market".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"nft_revoke","data":[{"owner_id":"bob","token_id":"3","account_id":"market"}]}"#
+        );
+
+        let log = NearEvent::nft_revoke_all(vec![NftRevokeAllData {
+            owner_id: "bob".to_string(),
+            token_id: "3".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"nft_revoke_all","data":[{"owner_id":"bob","token_id":"3"}]}"#
+        );
+    }
+
+    #[test]
+    fn listing() {
+        let log = NearEvent::listing_create(vec![ListingData {
+            owner_id: "bob".to_string(),
+            token_id: "3".to_string(),
+            price: "5000".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"listing_create","data":[{"owner_id":"bob","token_id":"3","price":"5000"}]}"#
+        );
+
+        let log = NearEvent::listing_remove(vec![ListingRemoveData {
+            owner_id: "bob".to_string(),
+            token_id: "3".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"listing_remove","data":[{"owner_id":"bob","token_id":"3"}]}"#
+        );
+
+        let log = NearEvent::listing_sold(vec![ListingSoldData {
+            seller_id: "bob".to_string(),
+            buyer_id: "alice".to_string(),
+            token_id: "3".to_string(),
+            price: "5000".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"listing_sold","data":[{"seller_id":"bob","buyer_id":"alice","token_id":"3","price":"5000"}]}"#
+        );
+    }
+
+    #[test]
+    fn auction() {
+        let log = NearEvent::auction_create(vec![AuctionCreateData {
+            index: 7,
+            reserve_price: "5000".to_string(),
+            start_at: "1000".to_string(),
+            end_at: "2000".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"auction_create","data":[{"index":7,"reserve_price":"5000","start_at":"1000","end_at":"2000"}]}"#
+        );
+
+        let log = NearEvent::auction_bid(vec![AuctionBidData {
+            index: 7,
+            bidder_id: "bob".to_string(),
+            amount: "6000".to_string(),
+            end_at: "2100".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"auction_bid","data":[{"index":7,"bidder_id":"bob","amount":"6000","end_at":"2100"}]}"#
+        );
+
+        let log = NearEvent::auction_settle(vec![
+            AuctionSettleData {
+                index: 7,
+                winner_id: Some("bob".to_string()),
+                amount: "6000".to_string(),
+            },
+            AuctionSettleData {
+                index: 8,
+                winner_id: None,
+                amount: "0".to_string(),
+            },
+        ])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"auction_settle","data":[{"index":7,"winner_id":"bob","amount":"6000"},{"index":8,"amount":"0"}]}"#
+        );
+    }
+
+    #[test]
+    fn stake() {
+        let log = NearEvent::stake(vec![StakeData {
+            owner_id: "bob".to_string(),
+            token_ids: make_tokens(vec!["3", "7"]),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"stake","data":[{"owner_id":"bob","token_ids":["3","7"]}]}"#
+        );
+
+        let log = NearEvent::unstake(vec![StakeData {
+            owner_id: "bob".to_string(),
+            token_ids: make_tokens(vec!["3"]),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"unstake","data":[{"owner_id":"bob","token_ids":["3"]}]}"#
+        );
+    }
+
+    #[test]
+    fn points_spend() {
+        let log = NearEvent::points_spend(vec![PointsSpendData {
+            account_id: "bob".to_string(),
+            points: "500".to_string(),
+            discount: "500000".to_string(),
+        }])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"mystery_box","version":"1.0.0","event":"points_spend","data":[{"account_id":"bob","points":"500","discount":"500000"}]}"#
+        );
+    }
 }
@@ -0,0 +1,311 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    serde::{Serialize, Deserialize},
+    near_bindgen, env, require, assert_one_yocto,
+};
+use near_contract_standards::non_fungible_token::Token;
+
+/// --- Fusion
+/// Holders can burn a set of items to mint one from the fusion pool,
+/// a separate `Raffle` set up once by the owner.
+/// A recipe either takes `count` items of a tier, i.e. with index
+/// in `[from_index, to_index)`, or exactly the items in `indices`.
+/// Items of the fusion pool get token ids after the main pool,
+/// so fusion item `i` is token `total + i`.
+/// Staked tokens can't be fused, and fused tokens can't be refunded.
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum FusionRecipe {
+    Tier {
+        from_index: u64,
+        to_index: u64,
+        count: u64,
+    },
+    Items {
+        indices: Vec<u64>,
+    },
+}
+
+impl FusionRecipe {
+    pub fn assert_valid(&self) {
+        let valid = match self {
+            FusionRecipe::Tier { from_index, to_index, count } => {
+                from_index < to_index && *count > 0
+            },
+            FusionRecipe::Items { indices } => {
+                let mut sorted = indices.clone();
+                sorted.sort_unstable();
+                sorted.dedup();
+                !indices.is_empty() && sorted.len() == indices.len()
+            },
+        };
+        require!(valid, error::ERR_BAD_RECIPE);
+    }
+
+    /// `indices` should have no duplicates
+    pub fn matches(
+        &self,
+        indices: &[u64]
+    ) -> bool {
+        match self {
+            FusionRecipe::Tier { from_index, to_index, count } => {
+                indices.len() as u64 == *count &&
+                    indices.iter().all(|i| from_index <= i && i < to_index)
+            },
+            FusionRecipe::Items { indices: required } => {
+                required.len() == indices.len() &&
+                    required.iter().all(|i| indices.contains(i))
+            },
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// set up the fusion pool with `len` items, can only be done once
+    #[payable]
+    pub fn init_fusion_pool(
+        &mut self,
+        len: u64
+    ) {
+        assert_one_yocto();
+        self.assert_role(Role::Admin);
+        require!(self.fusion_pool.is_none(), error::ERR_FUSION_POOL_EXISTS);
+        require!(len > 0, error::ERR_NO_ENOUGH_ITEMS);
+
+        self.fusion_pool = Some(Raffle::new(StorageKey::FusionPool, len));
+    }
+
+    /// none removes the recipe
+    #[payable]
+    pub fn set_fusion_recipe(
+        &mut self,
+        name: String,
+        recipe: Option<FusionRecipe>
+    ) {
+        assert_one_yocto();
+        self.assert_role(Role::Admin);
+
+        match recipe {
+            Some(recipe) => {
+                recipe.assert_valid();
+                self.fusion_recipes.insert(&name, &recipe);
+            },
+            None => {
+                self.fusion_recipes.remove(&name)
+                    .expect(error::ERR_NO_RECIPE);
+            },
+        }
+    }
+
+    /// burn tokens matching the recipe, and mint an item
+    /// drawn from the fusion pool, storage is paid by caller
+    #[payable]
+    pub fn fuse(
+        &mut self,
+        recipe: String,
+        token_ids: Vec<TokenId>
+    ) -> Token {
+        self.assert_not_paused(PauseTarget::Buy);
+        let fusion_recipe = self.fusion_recipes.get(&recipe)
+            .expect(error::ERR_NO_RECIPE);
+        require!(self.fusion_pool.is_some(), error::ERR_NO_FUSION_POOL);
+        let owner_id = env::predecessor_account_id();
+
+        let mut indices: Vec<u64> = vec![];
+        for token_id in token_ids.iter() {
+            require!(
+                self.owner_of(token_id) == Some(owner_id.clone()),
+                error::ERR_NOT_TOKEN_OWNER
+            );
+            self.assert_not_staked(token_id);
+
+            let index = util::item_index(token_id);
+            require!(!indices.contains(&index), error::ERR_RECIPE_NOT_MATCHED);
+            indices.push(index);
+        }
+        require!(fusion_recipe.matches(&indices), error::ERR_RECIPE_NOT_MATCHED);
+
+        for token_id in token_ids.iter() {
+            self.internal_burn(token_id, &owner_id);
+        }
+        event::NearEvent::log_nft_burn(
+            owner_id.to_string(),
+            token_ids,
+            Some(recipe.clone()),
+            None
+        );
+
+        // storage is measured after burning,
+        // so the caller only pays for the new token
+        let init_storage_usage = env::storage_usage();
+
+        let item = self.fusion_pool.as_mut()
+            .expect(error::ERR_NO_FUSION_POOL)
+            .draw();
+        let token_id = self.raffle.total() + item;
+        let token = self.mint_to(&token_id, &owner_id);
+
+        event::NearEvent::log_nft_mint(
+            owner_id.to_string(),
+            vec![token_id.to_string()],
+            Some(recipe)
+        );
+        mint::log_reveal(std::slice::from_ref(&token));
+
+        self.refund_storage_deposit(
+            init_storage_usage,
+            env::attached_deposit(),
+            &owner_id
+        );
+
+        token
+    }
+
+    pub fn fusion_recipes(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(String, FusionRecipe)> {
+        self.fusion_recipes.iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(u64::MAX) as usize)
+            .collect()
+    }
+
+    /// items left in the fusion pool, none if it's not set up
+    pub fn fusion_items_left(
+        &self
+    ) -> Option<u64> {
+        self.fusion_pool.as_ref().map(|pool| pool.items_left())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::escrow::{Escrow, EscrowConfig};
+    use crate::sale::SalePhase;
+    use crate::test_utils::{setup, setup_token, mint, set_context, set_predecessor};
+    use near_sdk::json_types::U64;
+    use near_sdk::test_utils::{accounts, get_logs};
+
+    /// bob holds items 0 and 1, which fuse by the "pair" recipe
+    /// into one of the 3 items of the fusion pool
+    fn setup_fusion() -> Contract {
+        let mut contract = setup_token();
+        mint(&mut contract, "1", accounts(1));
+
+        set_predecessor(accounts(0));
+        contract.init_fusion_pool(3);
+        contract.set_fusion_recipe(
+            "pair".to_string(),
+            Some(FusionRecipe::Items { indices: vec![0, 1] })
+        );
+        contract
+    }
+
+    #[test]
+    fn match_recipe() {
+        let tier = FusionRecipe::Tier { from_index: 2, to_index: 5, count: 2 };
+        assert!(tier.matches(&[2, 4]));
+        assert!(!tier.matches(&[2]));
+        assert!(!tier.matches(&[2, 3, 4]));
+        assert!(!tier.matches(&[4, 5]));
+
+        let items = FusionRecipe::Items { indices: vec![1, 7] };
+        assert!(items.matches(&[7, 1]));
+        assert!(!items.matches(&[1]));
+        assert!(!items.matches(&[1, 8]));
+    }
+
+    #[test]
+    fn fuse() {
+        let mut contract = setup_fusion();
+        assert_eq!(1, contract.fusion_recipes(None, None).len());
+
+        // both were bought in an escrowed sale
+        let tokens: Vec<Token> = ["0", "1"].iter()
+            .map(|token_id| contract.nft_token(token_id.to_string()).unwrap())
+            .collect();
+        contract.escrow = Some(Escrow::new(EscrowConfig { threshold: 5, deadline: U64(1000) }));
+        contract.record_purchase(&accounts(1), &tokens, 200, SalePhase::Open);
+        contract.hold_in_escrow(&tokens, 200, 0);
+
+        set_context(accounts(1), 10u128.pow(22));
+        let token = contract.fuse(
+            "pair".to_string(),
+            vec!["0".to_string(), "1".to_string()]
+        );
+
+        // fusion items come after the 10 items of the main pool
+        let token_id: u64 = token.token_id.parse().unwrap();
+        assert!((10..13).contains(&token_id));
+        assert_eq!(Some(2), contract.fusion_items_left());
+        assert!(contract.owner_of(&"0".to_string()).is_none());
+        assert!(contract.owner_of(&"1".to_string()).is_none());
+        assert_eq!(accounts(1), contract.owner_of(&token.token_id).unwrap());
+        assert!(contract.refund_of("0".to_string()).is_none());
+        assert!(contract.refund_of("1".to_string()).is_none());
+        assert!(contract.purchase_of_token("0".to_string()).is_none());
+        assert!(contract.purchase_of_token("1".to_string()).is_none());
+
+        let logs = get_logs();
+        assert!(logs.iter().any(|log| log.contains(r#""event":"nft_burn","data":[{"owner_id":"bob","token_ids":["0","1"],"memo":"pair"}]"#)));
+        assert!(logs.iter().any(|log| log.contains(r#""event":"nft_mint""#)));
+    }
+
+    #[test]
+    #[should_panic(expected = "E63: tokens don't match the recipe.")]
+    fn fuse_not_matched() {
+        let mut contract = setup_fusion();
+        mint(&mut contract, "2", accounts(1));
+
+        set_context(accounts(1), 10u128.pow(22));
+        contract.fuse(
+            "pair".to_string(),
+            vec!["0".to_string(), "2".to_string()]
+        );
+    }
+
+    #[test]
+    fn admin_sets_up_fusion() {
+        let mut contract = setup();
+        set_predecessor(accounts(0));
+        contract.grant_role(accounts(2), Role::Admin);
+
+        set_predecessor(accounts(2));
+        contract.init_fusion_pool(3);
+        contract.set_fusion_recipe(
+            "pair".to_string(),
+            Some(FusionRecipe::Items { indices: vec![0, 1] })
+        );
+        assert_eq!(Some(3), contract.fusion_items_left());
+        assert_eq!(1, contract.fusion_recipes(None, None).len());
+    }
+
+    #[test]
+    #[should_panic(expected = "E23: missing role. Require admin.")]
+    fn init_fusion_pool_not_admin() {
+        let mut contract = setup();
+        set_predecessor(accounts(1));
+        contract.init_fusion_pool(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "E26: buy is paused.")]
+    fn fuse_paused() {
+        let mut contract = setup_fusion();
+        contract.pause(vec![PauseTarget::Buy]);
+
+        set_context(accounts(1), 10u128.pow(22));
+        contract.fuse(
+            "pair".to_string(),
+            vec!["0".to_string(), "1".to_string()]
+        );
+    }
+}
@@ -1,14 +1,30 @@
 use crate::raffle::Raffle;
 use crate::royalty::{Royalty, RoyaltyMap, Percentage,};
+use crate::pricing::{PriceSchedule, DiscountTier};
+use crate::rebate::RebateRecord;
+use crate::referral::{ReferralConfig, ReferralStats};
+use crate::promo::PromoCode;
+use crate::access::Role;
+use crate::pause::{PauseState, PauseTarget};
+use crate::escrow::{Escrow, EscrowConfig, EscrowPayment};
+use crate::purchase::Purchase;
+use crate::sale::SalePhase;
+use crate::lock::TransferLock;
+use crate::market::Listing;
+use crate::auction::Auction;
+use crate::staking::{StakingConfig, Staker};
+use crate::fusion::FusionRecipe;
 use crate::constant::*;
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    near_bindgen, env, PanicOnDefault, Promise, PromiseOrValue,
-    BorshStorageKey, ext_contract, assert_one_yocto,
-    AccountId, Balance,
+    near_bindgen, env, PanicOnDefault, Promise,
+    BorshStorageKey, ext_contract, assert_one_yocto, require,
+    AccountId, Balance, CryptoHash, collections::{LookupMap, UnorderedMap, UnorderedSet, Vector},
 };
 use near_contract_standards::non_fungible_token::{
     NonFungibleToken, TokenId, Token,
+    core::{NonFungibleTokenCore, NonFungibleTokenResolver},
+    approval::NonFungibleTokenApproval,
     metadata::{
         NFTContractMetadata,
         NonFungibleTokenMetadataProvider,
@@ -24,6 +40,28 @@ mod raffle;
 mod mint;
 mod buy;
 mod royalty;
+mod pricing;
+mod rebate;
+mod referral;
+mod promo;
+mod access;
+mod pause;
+mod migrate;
+mod burn;
+mod escrow;
+mod sale;
+mod purchase;
+mod transfer;
+mod lock;
+mod approval;
+mod market;
+mod auction;
+mod staking;
+mod fusion;
+mod reroll;
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod test_utils;
 
 #[ext_contract(ext_nft_receiver)]
 trait NonFungibleTokenReceiver {
@@ -54,10 +92,49 @@ pub struct Contract {
     tokens: NonFungibleToken,
     metadata: NFTContractMetadata,
 
+    pending_owner: Option<AccountId>,
+    roles: LookupMap<AccountId, Vec<Role>>,
+    pause_state: PauseState,
+
     raffle: Raffle,
+    reserved: u64,
 
     royalty: Option<Royalty>,
 
+    price_schedule: PriceSchedule,
+    discount_tiers: Vec<DiscountTier>,
+    last_sale_price: Balance,
+    logged_phase: SalePhase,
+    rebates: LookupMap<AccountId, RebateRecord>,
+    rebate_reserve: Balance,
+
+    referral: Option<ReferralConfig>,
+    referrals: LookupMap<AccountId, ReferralStats>,
+
+    promo_codes: LookupMap<CryptoHash, PromoCode>,
+
+    escrow: Option<Escrow>,
+    paid_by_token: LookupMap<TokenId, EscrowPayment>,
+
+    purchases: Vector<Purchase>,
+    purchase_by_token: LookupMap<TokenId, u64>,
+    purchases_per_account: LookupMap<AccountId, Vector<u64>>,
+    unique_buyers: u64,
+    transfer_lock: Option<TransferLock>,
+    soulbound: bool,
+    approvals_by_account: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    listings: UnorderedMap<TokenId, Listing>,
+    listings_by_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    auctions: UnorderedMap<u64, Auction>,
+    bid_refunds: LookupMap<AccountId, Balance>,
+    staking: Option<StakingConfig>,
+    point_value: Balance,
+    stakers: LookupMap<AccountId, Staker>,
+    staked_by: LookupMap<TokenId, AccountId>,
+    fusion_pool: Option<Raffle>,
+    fusion_recipes: UnorderedMap<String, FusionRecipe>,
+    reroll_fee: Option<Balance>,
+
     total_income: Balance,
     distributed_income: Balance,
 }
@@ -69,25 +146,76 @@ enum StorageKey {
     Enumeration,
     Approval,
     Raffle,
+    Rebates,
+    Referrals,
+    PromoCodes,
+    Roles,
+    PaidByToken,
+    Purchases,
+    PurchaseByToken,
+    PurchasesPerAccount,
+    PurchasesPerAccountInner { account_hash: Vec<u8> },
+    ApprovalsByAccount,
+    ApprovalsByAccountInner { account_hash: Vec<u8> },
+    Listings,
+    ListingsByOwner,
+    ListingsByOwnerInner { account_hash: Vec<u8> },
+    Auctions,
+    BidRefunds,
+    Stakers,
+    StakedBy,
+    FusionPool,
+    FusionRecipes,
 }
 
 #[near_bindgen]
 impl Contract {
     #[init]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         metadata: NFTContractMetadata,
         len: u64,
         royalties: Option<RoyaltyMap>,
         royalty_rate: Option<Percentage>,
+        price_schedule: Option<PriceSchedule>,
+        discount_tiers: Option<Vec<DiscountTier>>,
+        referral: Option<ReferralConfig>,
+        reserved: Option<u64>,
+        escrow: Option<EscrowConfig>,
+        transfer_lock: Option<TransferLock>,
+        soulbound: Option<bool>,
     ) -> Self {
         metadata.assert_valid();
 
+        let price_schedule = price_schedule.unwrap_or_default();
+        price_schedule.assert_valid();
+
+        let discount_tiers = discount_tiers.unwrap_or_default();
+        pricing::assert_valid_discount_tiers(&discount_tiers);
+
+        if let Some(config) = referral.as_ref() {
+            config.assert_valid();
+        }
+
+        let reserved = reserved.unwrap_or(0);
+        require!(reserved <= len, error::ERR_NO_ENOUGH_ITEMS);
+
+        if let Some(config) = escrow.as_ref() {
+            config.assert_valid(len - reserved);
+        }
+
+        // refunds can't be combined with dutch auction rebates
+        require!(
+            escrow.is_none() || price_schedule.rebate_floor().is_none(),
+            error::ERR_BAD_ESCROW_CONFIG
+        );
+
         let owner_id = env::predecessor_account_id();
         let royalty = royalty_rate.map(|rate| {
             Royalty::new(royalties.unwrap(), rate)
         });
 
-        Self {
+        let mut contract = Self {
             tokens: NonFungibleToken::new(
                 StorageKey::OwnerById,
                 owner_id,
@@ -96,11 +224,47 @@ impl Contract {
                 Some(StorageKey::Approval)
             ),
             metadata: metadata,
+            pending_owner: None,
+            roles: LookupMap::new(StorageKey::Roles),
+            pause_state: PauseState::default(),
             raffle: Raffle::new(StorageKey::Raffle, len),
+            reserved,
             royalty: royalty,
+            price_schedule,
+            discount_tiers,
+            last_sale_price: 0,
+            logged_phase: SalePhase::Open,
+            rebates: LookupMap::new(StorageKey::Rebates),
+            rebate_reserve: 0,
+            referral,
+            referrals: LookupMap::new(StorageKey::Referrals),
+            promo_codes: LookupMap::new(StorageKey::PromoCodes),
+            escrow: escrow.map(Escrow::new),
+            paid_by_token: LookupMap::new(StorageKey::PaidByToken),
+            purchases: Vector::new(StorageKey::Purchases),
+            purchase_by_token: LookupMap::new(StorageKey::PurchaseByToken),
+            purchases_per_account: LookupMap::new(StorageKey::PurchasesPerAccount),
+            unique_buyers: 0,
+            transfer_lock,
+            soulbound: soulbound.unwrap_or(false),
+            approvals_by_account: LookupMap::new(StorageKey::ApprovalsByAccount),
+            listings: UnorderedMap::new(StorageKey::Listings),
+            listings_by_owner: LookupMap::new(StorageKey::ListingsByOwner),
+            auctions: UnorderedMap::new(StorageKey::Auctions),
+            bid_refunds: LookupMap::new(StorageKey::BidRefunds),
+            staking: None,
+            point_value: 0,
+            stakers: LookupMap::new(StorageKey::Stakers),
+            staked_by: LookupMap::new(StorageKey::StakedBy),
+            fusion_pool: None,
+            fusion_recipes: UnorderedMap::new(StorageKey::FusionRecipes),
+            reroll_fee: None,
             total_income: 0,
             distributed_income: 0,
-        }
+        };
+        contract.logged_phase = contract.sale_phase();
+
+        contract
     }
 
     fn owner_of(
@@ -111,8 +275,6 @@ impl Contract {
     }
 }
 
-near_contract_standards::impl_non_fungible_token_core!(Contract, tokens);
-near_contract_standards::impl_non_fungible_token_approval!(Contract, tokens);
 near_contract_standards::impl_non_fungible_token_enumeration!(Contract, tokens);
 
 #[near_bindgen]
@@ -122,115 +284,21 @@ impl NonFungibleTokenMetadataProvider for Contract {
     }
 }
 
+#[near_bindgen]
 impl Contract {
-    /// override default nft transfer to log evnet
-    pub fn nft_transfer(
+    /// owner replaces contract metadata, e.g. to move icon
+    /// or base_uri to another host
+    #[payable]
+    pub fn set_contract_metadata(
         &mut self,
-        receiver_id: AccountId,
-        token_id: TokenId,
-        approval_id: Option<u64>,
-        memo: Option<String>,
+        metadata: NFTContractMetadata
     ) {
-        let old_owner_id = self.owner_of(&token_id)
-            .expect(error::ERR_TOKEN_NOT_EXIST);
-
-        let sender_id = env::predecessor_account_id();
-        let authorized_id : Option<AccountId> = if sender_id != old_owner_id {
-            Some(sender_id.clone())
-        } else {
-            None
-        };
-
-        self.tokens.nft_transfer(
-            receiver_id.clone(),
-            token_id.clone(),
-            approval_id,
-            memo.clone()
-        );
-
-        event::NearEvent::log_nft_transfer(
-            old_owner_id.to_string(),
-            receiver_id.to_string(),
-            vec![token_id],
-            memo,
-            authorized_id.map(|id| id.to_string())
-        );
-    }
-
-    pub fn nft_transfer_call(
-        &mut self,
-        receiver_id: AccountId,
-        token_id: TokenId,
-        approval_id: Option<u64>,
-        memo: Option<String>,
-        msg: String,
-    ) -> PromiseOrValue<bool> {
         assert_one_yocto();
+        self.assert_owner();
+        metadata.assert_valid();
 
-        // this is to make sure error logs are consistant
-        let old_owner_id = self.owner_of(&token_id)
-            .expect(error::ERR_TOKEN_NOT_EXIST);
-
-        let sender_id = env::predecessor_account_id();
-        let (_, old_approvals) = self.tokens.internal_transfer(
-            &sender_id,
-            &receiver_id,
-            &token_id,
-            approval_id,
-            memo.clone()
-        );
-
-        ext_nft_receiver::nft_on_transfer(
-            sender_id,
-            old_owner_id.clone(),
-            token_id.clone(),
-            msg,
-            receiver_id.clone(),
-            NO_DEPOSIT,
-            env::prepaid_gas() 
-                - GAS_FOR_NFT_TRANSFER_CALL 
-                - GAS_FOR_RESOLVE_TRANSFER,
-        )
-        .then(ext_self::nft_resolve_transfer(
-            old_owner_id,
-            receiver_id.into(),
-            token_id,
-            old_approvals,
-            env::current_account_id(),
-            NO_DEPOSIT,
-            GAS_FOR_RESOLVE_TRANSFER,
-        ))
-        .into()
-    }
-}
-
-#[near_bindgen]
-impl Contract {
-    #[allow(dead_code)]
-    fn nft_resolve_transfer(
-        &mut self,
-        previous_owner_id: AccountId,
-        receiver_id: AccountId,
-        token_id: TokenId,
-        approved_account_ids: Option<HashMap<AccountId, u64>>,
-    ) -> bool {
-        let success = self.tokens.nft_resolve_transfer(
-            previous_owner_id.clone(),
-            receiver_id.clone(),
-            token_id.clone(),
-            approved_account_ids
-        );
-
-        if success {
-            event::NearEvent::log_nft_transfer(
-                previous_owner_id.to_string(),
-                receiver_id.to_string(),
-                vec![token_id],
-                None,
-                None
-            );
-        }
+        self.metadata = metadata;
 
-        return success;
+        event::NearEvent::log_contract_metadata_update(None);
     }
 }
@@ -0,0 +1,129 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    serde::{Serialize, Deserialize},
+    json_types::U64,
+    near_bindgen, env, require, Timestamp,
+};
+
+/// --- TransferLock
+/// Optionally, tokens can't be transferred for a while after the drop.
+/// `Until` locks every token until a fixed `timestamp`.
+/// `Duration` locks each token for `duration` nanoseconds
+/// after it's minted, as recorded in `issued_at`.
+/// A soulbound collection can't be transferred or approved at all,
+/// holders can only burn their tokens.
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum TransferLock {
+    Until {
+        timestamp: U64,
+    },
+    Duration {
+        duration: U64,
+    },
+}
+
+impl TransferLock {
+    /// tokens without `issued_at` are never locked by duration
+    pub fn unlock_at(
+        &self,
+        issued_at: Option<Timestamp>
+    ) -> Timestamp {
+        match self {
+            TransferLock::Until { timestamp } => timestamp.0,
+            TransferLock::Duration { duration } => {
+                issued_at.map_or(0, |issued_at| issued_at.saturating_add(duration.0))
+            }
+        }
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_transferable(
+        &self,
+        token_id: &TokenId
+    ) {
+        self.assert_not_soulbound();
+        self.assert_unlocked(token_id);
+        self.assert_not_staked(token_id);
+    }
+
+    pub(crate) fn assert_not_soulbound(
+        &self
+    ) {
+        require!(!self.soulbound, error::ERR_SOULBOUND);
+    }
+
+    fn assert_unlocked(
+        &self,
+        token_id: &TokenId
+    ) {
+        if let Some(unlock_at) = self.unlock_time(token_id.clone()) {
+            require!(
+                env::block_timestamp() >= unlock_at.0,
+                error::ERR_TOKEN_LOCKED
+            );
+        }
+    }
+
+    fn issued_at(
+        &self,
+        token_id: &TokenId
+    ) -> Option<Timestamp> {
+        self.tokens.token_metadata_by_id.as_ref()
+            .and_then(|by_id| by_id.get(token_id))
+            .and_then(|metadata| metadata.issued_at)
+            .and_then(|issued_at| issued_at.parse().ok())
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn is_soulbound(
+        &self
+    ) -> bool {
+        self.soulbound
+    }
+
+    pub fn transfer_lock(
+        &self
+    ) -> Option<TransferLock> {
+        self.transfer_lock.clone()
+    }
+
+    /// the time a token becomes transferable,
+    /// none if there is no lock or the token doesn't exist
+    pub fn unlock_time(
+        &self,
+        token_id: TokenId
+    ) -> Option<U64> {
+        let lock = self.transfer_lock.as_ref()?;
+        self.owner_of(&token_id)?;
+
+        Some(lock.unlock_at(self.issued_at(&token_id)).into())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_until() {
+        let lock = TransferLock::Until { timestamp: U64(1000) };
+        assert_eq!(1000, lock.unlock_at(Some(5000)));
+        assert_eq!(1000, lock.unlock_at(None));
+    }
+
+    #[test]
+    fn lock_duration() {
+        let lock = TransferLock::Duration { duration: U64(1000) };
+        assert_eq!(6000, lock.unlock_at(Some(5000)));
+        assert_eq!(u64::MAX, lock.unlock_at(Some(u64::MAX - 1)));
+        assert_eq!(0, lock.unlock_at(None));
+    }
+}
@@ -0,0 +1,377 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    serde::{Serialize, Deserialize},
+    collections::UnorderedSet,
+    json_types::U128,
+    near_bindgen, env, require, assert_one_yocto, Balance, Promise,
+};
+use crate::royalty::{Payout, RoyaltyMap};
+
+/// --- Market
+/// Holders can list their tokens for a fixed price,
+/// and anyone can buy a listed token by paying that price.
+/// Royalties are paid out of the price just like on any other
+/// marketplace, and the seller gets the rest.
+/// A listing goes away as soon as the token leaves its owner
+/// through any other path, and the storage it took is refunded
+/// to the seller.
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Listing {
+    pub token_id: TokenId,
+    pub owner_id: AccountId,
+    pub price: U128,
+}
+
+impl Contract {
+    /// remove the listing of a token if there is one,
+    /// refund its storage to the seller and return the removed listing
+    pub(crate) fn remove_listing(
+        &mut self,
+        token_id: &TokenId
+    ) -> Option<Listing> {
+        let init_storage_usage = env::storage_usage();
+        let listing = self.listings.remove(token_id)?;
+
+        if let Some(mut token_ids) = self.listings_by_owner.get(&listing.owner_id) {
+            token_ids.remove(token_id);
+            if token_ids.is_empty() {
+                self.listings_by_owner.remove(&listing.owner_id);
+            } else {
+                self.listings_by_owner.insert(&listing.owner_id, &token_ids);
+            }
+        }
+
+        let storage_refund = env::storage_byte_cost() *
+            Balance::from(init_storage_usage - env::storage_usage());
+        if storage_refund > 0 {
+            Promise::new(listing.owner_id.clone()).transfer(storage_refund);
+        }
+
+        Some(listing)
+    }
+
+    /// called on every transfer and burn
+    pub(crate) fn cancel_listing(
+        &mut self,
+        token_id: &TokenId
+    ) {
+        if let Some(listing) = self.remove_listing(token_id) {
+            event::NearEvent::log_listing_remove(
+                listing.owner_id.to_string(),
+                listing.token_id
+            );
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// list a token for sale, or update its price,
+    /// storage is paid by the owner
+    #[payable]
+    pub fn list(
+        &mut self,
+        token_id: TokenId,
+        price: U128
+    ) {
+        let owner_id = self.owner_of(&token_id)
+            .expect(error::ERR_TOKEN_NOT_EXIST);
+        require!(
+            owner_id == env::predecessor_account_id(),
+            error::ERR_NOT_TOKEN_OWNER
+        );
+        require!(price.0 > 0, error::ERR_BAD_LISTING_PRICE);
+        self.assert_transferable(&token_id);
+
+        let init_storage_usage = env::storage_usage();
+
+        self.listings.insert(&token_id, &Listing {
+            token_id: token_id.clone(),
+            owner_id: owner_id.clone(),
+            price,
+        });
+
+        let mut token_ids = self.listings_by_owner.get(&owner_id)
+            .unwrap_or_else(|| {
+                UnorderedSet::new(StorageKey::ListingsByOwnerInner {
+                    account_hash: env::sha256(owner_id.as_bytes()),
+                })
+            });
+        token_ids.insert(&token_id);
+        self.listings_by_owner.insert(&owner_id, &token_ids);
+
+        self.refund_storage_deposit(
+            init_storage_usage,
+            env::attached_deposit(),
+            &owner_id
+        );
+
+        event::NearEvent::log_listing_create(
+            owner_id.to_string(),
+            token_id,
+            price.0.to_string()
+        );
+    }
+
+    #[payable]
+    pub fn delist(
+        &mut self,
+        token_id: TokenId
+    ) {
+        assert_one_yocto();
+
+        let listing = self.listings.get(&token_id)
+            .expect(error::ERR_NOT_LISTED);
+        require!(
+            listing.owner_id == env::predecessor_account_id(),
+            error::ERR_NOT_TOKEN_OWNER
+        );
+
+        self.cancel_listing(&token_id);
+    }
+
+    /// buy a listed token, extra deposit is refunded
+    #[payable]
+    pub fn buy_listed(
+        &mut self,
+        token_id: TokenId
+    ) {
+        self.assert_not_paused(PauseTarget::Transfer);
+
+        let listing = self.remove_listing(&token_id)
+            .expect(error::ERR_NOT_LISTED);
+        let price: Balance = listing.price.into();
+        let deposit = env::attached_deposit();
+        require!(
+            deposit >= price,
+            format!(
+                "{} Require {}.",
+                error::ERR_NO_ENOUGH_DEPOSIT,
+                price,
+            )
+        );
+
+        let buyer_id = env::predecessor_account_id();
+        require!(buyer_id != listing.owner_id, error::ERR_BUY_OWN_LISTING);
+
+        // without royalty the seller gets the full price
+        let payouts = match &self.royalty {
+            Some(royalty) => Payout::calculate_payout(
+                price,
+                &listing.owner_id,
+                royalty.get_royalties(),
+                royalty.get_royalty_rate()
+            ),
+            None => Payout::calculate_payout(
+                price,
+                &listing.owner_id,
+                &RoyaltyMap::new(),
+                0
+            ),
+        };
+
+        self.internal_transfer(
+            &listing.owner_id,
+            &buyer_id,
+            &token_id,
+            None,
+            None
+        );
+
+        for (account, amount) in payouts.payout.iter() {
+            if amount.0 > 0 {
+                Promise::new(account.clone()).transfer(amount.0);
+            }
+        }
+
+        let refund = deposit - price;
+        if refund > 0 {
+            Promise::new(buyer_id.clone()).transfer(refund);
+        }
+
+        event::NearEvent::log_listing_sold(
+            listing.owner_id.to_string(),
+            buyer_id.to_string(),
+            token_id,
+            price.to_string()
+        );
+    }
+
+    pub fn listing(
+        &self,
+        token_id: TokenId
+    ) -> Option<Listing> {
+        self.listings.get(&token_id)
+    }
+
+    pub fn listings(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<Listing> {
+        self.listings.values()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(u64::MAX) as usize)
+            .collect()
+    }
+
+    pub fn listings_by_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<Listing> {
+        let token_ids = match self.listings_by_owner.get(&account_id) {
+            Some(token_ids) => token_ids,
+            None => return vec![],
+        };
+
+        token_ids.iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(u64::MAX) as usize)
+            .filter_map(|token_id| self.listings.get(&token_id))
+            .collect()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::royalty::Royalty;
+    use crate::test_utils::{setup_token, mint, set_context, set_predecessor};
+    use near_sdk::test_utils::{accounts, get_logs, get_created_receipts};
+    use near_sdk::mock::VmAction;
+
+    const STORAGE_DEPOSIT: Balance = 10u128.pow(22);
+
+    /// every NEAR transfer made so far, by receiver
+    fn transfers() -> Vec<(AccountId, Balance)> {
+        let mut transfers: Vec<(AccountId, Balance)> = get_created_receipts().into_iter()
+            .flat_map(|receipt| {
+                let receiver_id = receipt.receiver_id;
+                receipt.actions.into_iter().filter_map(move |action| match action {
+                    VmAction::Transfer { deposit } => Some((receiver_id.clone(), deposit)),
+                    _ => None,
+                })
+            })
+            .collect();
+        transfers.sort();
+        transfers
+    }
+
+    /// list token "0" for 100 as bob,
+    /// return the storage cost bob paid for it
+    fn list_token(contract: &mut Contract) -> Balance {
+        set_context(accounts(1), STORAGE_DEPOSIT);
+        contract.list("0".to_string(), U128(100));
+        STORAGE_DEPOSIT - transfers()[0].1
+    }
+
+    fn listed_tokens(contract: &Contract) -> Vec<TokenId> {
+        let mut token_ids: Vec<TokenId> = contract.listings(None, None)
+            .into_iter()
+            .map(|listing| listing.token_id)
+            .collect();
+        token_ids.sort();
+        token_ids
+    }
+
+    #[test]
+    fn list_and_delist() {
+        let mut contract = setup_token();
+        mint(&mut contract, "1", accounts(1));
+
+        set_context(accounts(1), STORAGE_DEPOSIT);
+        contract.list("0".to_string(), U128(100));
+        contract.list("1".to_string(), U128(200));
+        assert_eq!(vec!["0", "1"], listed_tokens(&contract));
+        assert_eq!(2, contract.listings_by_owner(accounts(1), None, None).len());
+        assert_eq!(1, contract.listings(Some(1), Some(5)).len());
+
+        set_predecessor(accounts(1));
+        contract.delist("0".to_string());
+        assert_eq!(
+            get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"mystery_box","version":"1.0.0","event":"listing_remove","data":[{"owner_id":"bob","token_id":"0"}]}"#
+        );
+        assert_eq!(vec!["1"], listed_tokens(&contract));
+    }
+
+    #[test]
+    fn delist_refunds_storage() {
+        let mut contract = setup_token();
+        let storage_cost = list_token(&mut contract);
+        assert!(storage_cost > 0);
+
+        set_predecessor(accounts(1));
+        contract.delist("0".to_string());
+        assert_eq!(vec![(accounts(1), storage_cost)], transfers());
+    }
+
+    #[test]
+    fn transfer_removes_listing() {
+        let mut contract = setup_token();
+        let storage_cost = list_token(&mut contract);
+
+        set_predecessor(accounts(1));
+        contract.nft_transfer(accounts(2), "0".to_string(), None, None);
+
+        assert!(contract.listing("0".to_string()).is_none());
+        assert!(contract.listings_by_owner(accounts(1), None, None).is_empty());
+        assert_eq!(vec![(accounts(1), storage_cost)], transfers());
+    }
+
+    #[test]
+    fn buy_listed() {
+        let mut contract = setup_token();
+        let storage_cost = list_token(&mut contract);
+
+        set_context(accounts(2), 150);
+        contract.buy_listed("0".to_string());
+
+        assert_eq!(accounts(2), contract.owner_of(&"0".to_string()).unwrap());
+        assert!(listed_tokens(&contract).is_empty());
+        assert_eq!(
+            get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"mystery_box","version":"1.0.0","event":"listing_sold","data":[{"seller_id":"bob","buyer_id":"charlie","token_id":"0","price":"100"}]}"#
+        );
+        // no royalty, seller gets the full price and buyer the change
+        assert_eq!(
+            vec![(accounts(1), 100), (accounts(1), storage_cost), (accounts(2), 50)],
+            transfers()
+        );
+    }
+
+    #[test]
+    fn buy_listed_with_royalty() {
+        let mut contract = setup_token();
+        // 10% to danny
+        contract.royalty = Some(Royalty::new(
+            RoyaltyMap::from([(accounts(3), 10_000)]),
+            1_000
+        ));
+        let storage_cost = list_token(&mut contract);
+
+        set_context(accounts(2), 100);
+        contract.buy_listed("0".to_string());
+
+        assert_eq!(
+            vec![(accounts(1), 90), (accounts(1), storage_cost), (accounts(3), 10)],
+            transfers()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "E46: can't buy own listing.")]
+    fn buy_own_listing() {
+        let mut contract = setup_token();
+        list_token(&mut contract);
+
+        set_context(accounts(1), 100);
+        contract.buy_listed("0".to_string());
+    }
+}
@@ -0,0 +1,228 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshDeserialize},
+    collections::{LookupMap, UnorderedMap, Vector},
+    near_bindgen, env, require, Balance,
+};
+
+/// --- Migration
+/// `migrate` reads the state written by the first release,
+/// which had no price schedule, roles, pause, etc.
+/// and fills every new field with its default.
+/// When the layout of `Contract` changes again, the old layout
+/// here should be replaced by the one being upgraded from.
+
+#[derive(BorshDeserialize)]
+#[cfg_attr(test, derive(borsh::BorshSerialize))]
+struct RaffleV1 {
+    len: u64,
+    #[allow(dead_code)]
+    items: LookupMap<u64, u64>,
+}
+
+#[derive(BorshDeserialize)]
+#[cfg_attr(test, derive(borsh::BorshSerialize))]
+struct ContractV1 {
+    tokens: NonFungibleToken,
+    metadata: NFTContractMetadata,
+    raffle: RaffleV1,
+    royalty: Option<Royalty>,
+    total_income: Balance,
+    distributed_income: Balance,
+}
+
+#[near_bindgen]
+impl Contract {
+    #[init(ignore_state)]
+    #[private]
+    pub fn migrate() -> Self {
+        let old: ContractV1 = env::state_read()
+            .expect(error::ERR_NO_STATE);
+
+        // `len` counts down, every drawn item has been minted
+        let minted = old.tokens.owner_by_id.len();
+        let raffle = Raffle::resume(
+            StorageKey::Raffle,
+            old.raffle.len + minted,
+            old.raffle.len
+        );
+
+        let mut contract = Self {
+            tokens: old.tokens,
+            metadata: old.metadata,
+            pending_owner: None,
+            roles: LookupMap::new(StorageKey::Roles),
+            pause_state: PauseState::default(),
+            raffle,
+            reserved: 0,
+            royalty: old.royalty,
+            price_schedule: PriceSchedule::default(),
+            discount_tiers: vec![],
+            last_sale_price: 0,
+            logged_phase: SalePhase::Open,
+            rebates: LookupMap::new(StorageKey::Rebates),
+            rebate_reserve: 0,
+            referral: None,
+            referrals: LookupMap::new(StorageKey::Referrals),
+            promo_codes: LookupMap::new(StorageKey::PromoCodes),
+            escrow: None,
+            paid_by_token: LookupMap::new(StorageKey::PaidByToken),
+            purchases: Vector::new(StorageKey::Purchases),
+            purchase_by_token: LookupMap::new(StorageKey::PurchaseByToken),
+            purchases_per_account: LookupMap::new(StorageKey::PurchasesPerAccount),
+            // buyers before the ledger existed are not counted
+            unique_buyers: 0,
+            transfer_lock: None,
+            soulbound: false,
+            // approvals made before the index existed are not listed
+            approvals_by_account: LookupMap::new(StorageKey::ApprovalsByAccount),
+            listings: UnorderedMap::new(StorageKey::Listings),
+            listings_by_owner: LookupMap::new(StorageKey::ListingsByOwner),
+            auctions: UnorderedMap::new(StorageKey::Auctions),
+            bid_refunds: LookupMap::new(StorageKey::BidRefunds),
+            staking: None,
+            point_value: 0,
+            stakers: LookupMap::new(StorageKey::Stakers),
+            staked_by: LookupMap::new(StorageKey::StakedBy),
+            fusion_pool: None,
+            fusion_recipes: UnorderedMap::new(StorageKey::FusionRecipes),
+            reroll_fee: None,
+            total_income: old.total_income,
+            distributed_income: old.distributed_income,
+        };
+        contract.logged_phase = contract.sale_phase();
+
+        contract
+    }
+}
+
+/// owner deploys new code to this contract, and migrates state with it.
+/// the code is read from raw input rather than JSON to save gas.
+#[no_mangle]
+pub extern "C" fn upgrade() {
+    env::setup_panic_hook();
+
+    let contract: Contract = env::state_read()
+        .expect(error::ERR_NO_STATE);
+    contract.assert_owner();
+
+    let code = env::input()
+        .expect(error::ERR_NO_CODE);
+
+    // migrate must not run out of gas after the new code is deployed
+    let remaining_gas = env::prepaid_gas() - env::used_gas();
+    require!(remaining_gas > GAS_FOR_UPGRADE, error::ERR_NO_ENOUGH_GAS);
+
+    Promise::new(env::current_account_id())
+        .deploy_contract(code)
+        .function_call(
+            "migrate".to_string(),
+            vec![],
+            NO_DEPOSIT,
+            remaining_gas - GAS_FOR_UPGRADE
+        );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+    use near_contract_standards::non_fungible_token::metadata::{
+        NFT_METADATA_SPEC, TokenMetadata,
+    };
+
+    fn token_metadata(token_id: &str) -> TokenMetadata {
+        TokenMetadata {
+            title: Some(token_id.to_string()),
+            description: None,
+            media: None,
+            media_hash: None,
+            extra: None,
+            reference: None,
+            reference_hash: None,
+            copies: None,
+            expires_at: None,
+            issued_at: None,
+            starts_at: None,
+            updated_at: None
+        }
+    }
+
+    /// write state as the first release would after
+    /// selling token #3 and #9 out of 10
+    fn write_v1_state() {
+        let mut tokens = NonFungibleToken::new(
+            StorageKey::OwnerById,
+            accounts(0),
+            Some(StorageKey::TokenMetadata),
+            Some(StorageKey::Enumeration),
+            Some(StorageKey::Approval)
+        );
+        for token_id in ["3", "9"] {
+            tokens.internal_mint_with_refund(
+                token_id.to_string(),
+                accounts(1),
+                Some(token_metadata(token_id)),
+                None
+            );
+        }
+
+        // drew position 3 twice, swapping in 9 then 8
+        let mut items = LookupMap::new(StorageKey::Raffle);
+        items.insert(&3, &9);
+        items.insert(&3, &8);
+
+        let mut royalties = RoyaltyMap::new();
+        royalties.insert(accounts(2), royalty::PERCENTAGE_BASIS);
+
+        env::state_write(&ContractV1 {
+            tokens,
+            metadata: NFTContractMetadata {
+                spec: NFT_METADATA_SPEC.to_string(),
+                name: "test nft".to_string(),
+                symbol: "nft".to_string(),
+                icon: None,
+                base_uri: None,
+                reference: None,
+                reference_hash: None,
+            },
+            raffle: RaffleV1 { len: 8, items },
+            royalty: Some(Royalty::new(royalties, 1_000)),
+            total_income: 2_000,
+            distributed_income: 500,
+        });
+    }
+
+    #[test]
+    fn migrate_v1() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .build());
+        write_v1_state();
+
+        let mut contract = Contract::migrate();
+
+        assert_eq!(accounts(0), contract.owner());
+        assert_eq!("test nft", contract.nft_metadata().name);
+        assert_eq!(2_000, contract.total_income);
+        assert_eq!(500, contract.distributed_income);
+        assert_eq!(DEFAULT_UNIT_PRICE, contract.unit_price());
+
+        assert_eq!(8, contract.raffle.items_left());
+        assert_eq!(2, contract.raffle.items_drawn());
+        assert_eq!(8, contract.items_for_sale());
+
+        let token = contract.nft_token("9".to_string()).unwrap();
+        assert_eq!(accounts(1), token.owner_id);
+
+        // the rest of items can still be drawn
+        let mut results: Vec<u64> = (0..8)
+            .map(|_| contract.raffle.draw())
+            .collect();
+        results.sort_unstable();
+        assert_eq!(vec![0, 1, 2, 4, 5, 6, 7, 8], results);
+    }
+}
@@ -22,7 +22,7 @@ impl Contract {
             error::ERR_NO_ENOUGH_ITEMS
         );
 
-        let tokens = token_ids.iter()
+        let tokens: Vec<Token> = token_ids.iter()
             .map(|id| self.mint_to(id, owner_id))
             .collect();
 
@@ -31,13 +31,81 @@ impl Contract {
             token_ids.iter().map(|id| id.to_string()).collect(),
             None
         );
+        log_reveal(&tokens);
 
         return tokens;
     }
 
+    /// number of items kept out of public sale for airdrop
+    pub fn reserved(
+        &self
+    ) -> u64 {
+        self.reserved
+    }
+
+    /// owner draws and mints reserved items to receivers for free,
+    /// storage is paid by owner
+    #[payable]
+    pub fn airdrop(
+        &mut self,
+        receivers: Vec<(AccountId, u64)>
+    ) {
+        self.assert_role(Role::Minter);
+
+        let total: u64 = receivers.iter().map(|(_, n)| n).sum();
+        require!(total <= self.reserved, error::ERR_NO_ENOUGH_RESERVED);
+        self.reserved -= total;
+
+        let init_storage_usage = env::storage_usage();
+
+        for (receiver_id, n) in receivers.iter() {
+            self.mint_many(*n, receiver_id);
+        }
+
+        self.refund_storage_deposit(
+            init_storage_usage,
+            env::attached_deposit(),
+            &env::predecessor_account_id()
+        );
+    }
+
+    /// owner takes a specific reserved item out of the raffle,
+    /// and mints it to receiver for free, storage is paid by owner
+    #[payable]
+    pub fn mint_item(
+        &mut self,
+        index: u64,
+        receiver_id: AccountId
+    ) -> Token {
+        self.assert_role(Role::Minter);
+
+        require!(self.reserved > 0, error::ERR_NO_ENOUGH_RESERVED);
+        self.reserved -= 1;
+
+        let init_storage_usage = env::storage_usage();
+
+        self.raffle.take(index);
+        let token = self.mint_to(&index, &receiver_id);
+
+        event::NearEvent::log_nft_mint(
+            receiver_id.to_string(),
+            vec![index.to_string()],
+            None
+        );
+        log_reveal(std::slice::from_ref(&token));
+
+        self.refund_storage_deposit(
+            init_storage_usage,
+            env::attached_deposit(),
+            &env::predecessor_account_id()
+        );
+
+        token
+    }
+
     /// Mint NFT to owner
     /// storage refund should be handled by caller
-    fn mint_to(
+    pub(crate) fn mint_to(
         &mut self,
         token_id: &u64,
         owner_id: &AccountId
@@ -65,3 +133,22 @@ impl Contract {
         );
     }
 }
+
+/// what's inside each box, known once it's minted
+pub(crate) fn log_reveal(
+    tokens: &[Token]
+) {
+    let data = tokens.iter()
+        .map(|token| {
+            let metadata = token.metadata.as_ref();
+            event::RevealData {
+                owner_id: token.owner_id.to_string(),
+                token_id: token.token_id.clone(),
+                media: metadata.and_then(|m| m.media.clone()),
+                reference: metadata.and_then(|m| m.reference.clone()),
+            }
+        })
+        .collect();
+
+    event::NearEvent::log_reveals(data);
+}
@@ -0,0 +1,112 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    serde::{Serialize, Deserialize},
+    near_bindgen, require, assert_one_yocto,
+};
+
+/// --- Pause
+/// Kill switches for each group of entrypoints,
+/// controlled by the pause guardian.
+/// Revoking approvals is never paused, so that owners
+/// can always take back approvals they made.
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum PauseTarget {
+    Buy,
+    Transfer,
+    DistributeIncome,
+    Approval,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Default, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseState {
+    pub buy: bool,
+    pub transfer: bool,
+    pub distribute_income: bool,
+    pub approval: bool,
+}
+
+impl PauseState {
+    fn flag(
+        &mut self,
+        target: PauseTarget
+    ) -> &mut bool {
+        match target {
+            PauseTarget::Buy => &mut self.buy,
+            PauseTarget::Transfer => &mut self.transfer,
+            PauseTarget::DistributeIncome => &mut self.distribute_income,
+            PauseTarget::Approval => &mut self.approval,
+        }
+    }
+
+    fn is_paused(
+        &self,
+        target: PauseTarget
+    ) -> bool {
+        match target {
+            PauseTarget::Buy => self.buy,
+            PauseTarget::Transfer => self.transfer,
+            PauseTarget::DistributeIncome => self.distribute_income,
+            PauseTarget::Approval => self.approval,
+        }
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_not_paused(
+        &self,
+        target: PauseTarget
+    ) {
+        let err = match target {
+            PauseTarget::Buy => error::ERR_BUY_PAUSED,
+            PauseTarget::Transfer => error::ERR_TRANSFER_PAUSED,
+            PauseTarget::DistributeIncome => error::ERR_DISTRIBUTE_INCOME_PAUSED,
+            PauseTarget::Approval => error::ERR_APPROVAL_PAUSED,
+        };
+
+        require!(!self.pause_state.is_paused(target), err);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn pause_state(
+        &self
+    ) -> PauseState {
+        self.pause_state.clone()
+    }
+
+    #[payable]
+    pub fn pause(
+        &mut self,
+        targets: Vec<PauseTarget>
+    ) {
+        assert_one_yocto();
+        self.assert_role(Role::PauseGuardian);
+
+        for target in targets {
+            *self.pause_state.flag(target) = true;
+        }
+
+        self.update_phase();
+    }
+
+    #[payable]
+    pub fn unpause(
+        &mut self,
+        targets: Vec<PauseTarget>
+    ) {
+        assert_one_yocto();
+        self.assert_role(Role::PauseGuardian);
+
+        for target in targets {
+            *self.pause_state.flag(target) = false;
+        }
+
+        self.update_phase();
+    }
+}
@@ -0,0 +1,511 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    serde::{Serialize, Deserialize},
+    json_types::{U128, U64},
+    require, Balance, Timestamp,
+};
+use crate::royalty::{PERCENTAGE_BASIS, apply_percent};
+
+/// --- PriceSchedule
+/// Decides how much a single box costs at a given time.
+/// `Fixed` always charges the same price.
+/// `DutchAuction` starts at `start_price` and drops by
+/// `decrement` every `interval` nanoseconds after `start_at`,
+/// until it reaches `floor_price`.
+/// If `rebate` is set, early buyers can claim back the difference
+/// between what they paid and the final clearing price.
+/// `BondingCurve` rises as `Raffle::items_left()` goes down, starting
+/// at `base_price` when nothing is drawn, see `Curve` for how it grows.
+/// Prices and costs saturate instead of overflow.
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSchedule {
+    Fixed {
+        price: U128,
+    },
+    DutchAuction {
+        start_price: U128,
+        floor_price: U128,
+        decrement: U128,
+        interval: U64,
+        start_at: U64,
+        rebate: bool,
+    },
+    BondingCurve {
+        base_price: U128,
+        curve: Curve,
+    },
+}
+
+/// `Linear` adds `increment` to the price for each item drawn.
+/// `Exponential` raises the price by `rate` (in PERCENTAGE_BASIS)
+/// for each item drawn.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Curve {
+    Linear {
+        increment: U128,
+    },
+    Exponential {
+        rate: Percentage,
+    },
+}
+
+impl Curve {
+    fn price_at(
+        &self,
+        base_price: Balance,
+        drawn: u64
+    ) -> Balance {
+        match self {
+            Curve::Linear { increment } => {
+                base_price.saturating_add(
+                    increment.0.saturating_mul(Balance::from(drawn))
+                )
+            }
+            Curve::Exponential { rate } => {
+                let factor = pow_scaled(growth_factor(*rate), drawn);
+                base_price.saturating_mul(factor) / SCALE
+            }
+        }
+    }
+
+    /// cost of `n` boxes where the first one costs `first_price`
+    fn cost_from(
+        &self,
+        first_price: Balance,
+        n: u64
+    ) -> Balance {
+        match self {
+            Curve::Linear { increment } => {
+                // n * first_price + increment * (0 + 1 + ... + n - 1)
+                let steps = Balance::from(n) * Balance::from(n.saturating_sub(1)) / 2;
+                first_price.saturating_mul(Balance::from(n))
+                    .saturating_add(increment.0.saturating_mul(steps))
+            }
+            Curve::Exponential { rate } => {
+                // first_price * (factor ^ n - 1) / (factor - 1)
+                let factor = growth_factor(*rate);
+                if factor == SCALE {
+                    return first_price.saturating_mul(Balance::from(n));
+                }
+                first_price.checked_mul(pow_scaled(factor, n) - SCALE)
+                    .map_or(Balance::MAX, |total| total / (factor - SCALE))
+            }
+        }
+    }
+}
+
+/// `1 + rate` scaled by `SCALE`
+fn growth_factor(
+    rate: Percentage
+) -> Balance {
+    SCALE + SCALE * Balance::from(rate) / Balance::from(PERCENTAGE_BASIS)
+}
+
+/// fixed point scale used by exponential curve
+const SCALE: Balance = 1_000_000_000_000;
+
+/// `factor ^ exp` where `factor` is scaled by `SCALE`,
+/// saturates on overflow
+fn pow_scaled(
+    mut factor: Balance,
+    mut exp: u64
+) -> Balance {
+    let mut result = SCALE;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.saturating_mul(factor) / SCALE;
+        }
+        factor = factor.saturating_mul(factor) / SCALE;
+        exp >>= 1;
+    }
+    result
+}
+
+impl Default for PriceSchedule {
+    fn default() -> Self {
+        PriceSchedule::Fixed {
+            price: DEFAULT_UNIT_PRICE.into()
+        }
+    }
+}
+
+impl PriceSchedule {
+    pub fn assert_valid(&self) {
+        if let PriceSchedule::DutchAuction {
+            start_price, floor_price, decrement, interval, ..
+        } = self {
+            require!(
+                start_price.0 >= floor_price.0
+                    && decrement.0 > 0
+                    && interval.0 > 0,
+                error::ERR_BAD_PRICE_SCHEDULE
+            );
+        }
+    }
+
+    /// price of a single box at `now`,
+    /// with `drawn` items out of the raffle
+    pub fn price_at(
+        &self,
+        now: Timestamp,
+        drawn: u64
+    ) -> Balance {
+        match self {
+            PriceSchedule::Fixed { price } => price.0,
+            PriceSchedule::DutchAuction {
+                start_price, floor_price, decrement, interval, start_at, ..
+            } => {
+                let steps = now.saturating_sub(start_at.0) / interval.0;
+                let discount = decrement.0.saturating_mul(Balance::from(steps));
+
+                start_price.0
+                    .saturating_sub(discount)
+                    .max(floor_price.0)
+            }
+            PriceSchedule::BondingCurve { base_price, curve } => {
+                curve.price_at(base_price.0, drawn)
+            }
+        }
+    }
+
+    /// cost of the next `n` boxes at `now`,
+    /// with `drawn` items out of the raffle
+    pub fn cost_at(
+        &self,
+        now: Timestamp,
+        drawn: u64,
+        n: u64
+    ) -> Balance {
+        let first_price = self.price_at(now, drawn);
+
+        match self {
+            PriceSchedule::BondingCurve { curve, .. } => curve.cost_from(first_price, n),
+            // the price doesn't change within a single purchase
+            _ => first_price.saturating_mul(Balance::from(n)),
+        }
+    }
+
+    /// the time of the next price drop after `now`,
+    /// none if the price won't drop anymore
+    pub fn next_drop_at(
+        &self,
+        now: Timestamp
+    ) -> Option<Timestamp> {
+        match self {
+            PriceSchedule::Fixed { .. } => None,
+            PriceSchedule::BondingCurve { .. } => None,
+            PriceSchedule::DutchAuction {
+                floor_price, interval, start_at, ..
+            } => {
+                if self.price_at(now, 0) <= floor_price.0 {
+                    return None;
+                }
+                if now < start_at.0 {
+                    return Some(start_at.0 + interval.0);
+                }
+
+                let steps = (now - start_at.0) / interval.0;
+                Some(start_at.0 + (steps + 1) * interval.0)
+            }
+        }
+    }
+
+    /// floor price of a dutch auction with rebate enabled
+    pub fn rebate_floor(&self) -> Option<Balance> {
+        match self {
+            PriceSchedule::DutchAuction {
+                floor_price, rebate: true, ..
+            } => Some(floor_price.0),
+            _ => None,
+        }
+    }
+}
+
+/// --- DiscountTier
+/// Volume discount applied to a single purchase of
+/// at least `min_boxes` boxes, in PERCENTAGE_BASIS.
+/// When several tiers match, the one with largest
+/// `min_boxes` is used.
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DiscountTier {
+    pub min_boxes: u64,
+    pub discount: Percentage,
+}
+
+pub fn assert_valid_discount_tiers(
+    tiers: &[DiscountTier]
+) {
+    for tier in tiers {
+        require!(
+            tier.min_boxes > 0 && tier.discount <= PERCENTAGE_BASIS,
+            error::ERR_BAD_DISCOUNT_TIER
+        );
+    }
+}
+
+/// discount for buying `n` boxes, in PERCENTAGE_BASIS
+pub fn discount_for(
+    tiers: &[DiscountTier],
+    n: u64
+) -> Percentage {
+    tiers.iter()
+        .filter(|tier| tier.min_boxes <= n)
+        .max_by_key(|tier| tier.min_boxes)
+        .map_or(0, |tier| tier.discount)
+}
+
+/// cost of `n` boxes with volume discount applied
+pub fn apply_discount(
+    tiers: &[DiscountTier],
+    n: u64,
+    cost: Balance
+) -> Balance {
+    cost - apply_percent(cost, &discount_for(tiers, n))
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Quote {
+    pub base_cost: U128,
+    pub discount: U128,
+    pub mint_cost: U128,
+    pub storage_cost: U128,
+    pub total: U128,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceInfo {
+    pub current_price: U128,
+    pub next_drop_at: Option<U64>,
+}
+
+impl Contract {
+    /// log price_changed if the box price is no longer `old_price`
+    pub(crate) fn log_price_change(
+        &self,
+        old_price: Balance
+    ) {
+        let new_price = self.unit_price();
+        if new_price != old_price {
+            event::NearEvent::log_price_changed(
+                old_price.to_string(),
+                new_price.to_string()
+            );
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn price_schedule(
+        &self
+    ) -> PriceSchedule {
+        self.price_schedule.clone()
+    }
+
+    /// current box price and the time it drops next
+    pub fn price_info(
+        &self
+    ) -> PriceInfo {
+        let now = env::block_timestamp();
+
+        PriceInfo {
+            current_price: self.unit_price().into(),
+            next_drop_at: self.price_schedule.next_drop_at(now).map(U64),
+        }
+    }
+
+    pub fn discount_tiers(
+        &self
+    ) -> Vec<DiscountTier> {
+        self.discount_tiers.clone()
+    }
+
+    /// price breakdown for buying `n` boxes,
+    /// optionally with a promo code
+    pub fn quote(
+        &self,
+        n: u64,
+        promo_code: Option<String>,
+    ) -> Quote {
+        let base_cost = self.price_schedule.cost_at(
+            env::block_timestamp(),
+            self.raffle.items_drawn(),
+            n
+        );
+        let mint_cost = promo_code.map_or(
+            self.mint_cost_for(n),
+            |code| self.promo_code_cost(&code, self.mint_cost_for(n))
+        );
+        let storage_cost = self.storage_cost_for(n);
+
+        Quote {
+            base_cost: base_cost.into(),
+            discount: (base_cost - mint_cost).into(),
+            mint_cost: mint_cost.into(),
+            storage_cost: storage_cost.into(),
+            total: (mint_cost + storage_cost).into(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup;
+
+    fn dutch_auction(rebate: bool) -> PriceSchedule {
+        PriceSchedule::DutchAuction {
+            start_price: U128(100),
+            floor_price: U128(40),
+            decrement: U128(25),
+            interval: U64(10),
+            start_at: U64(1000),
+            rebate,
+        }
+    }
+
+    #[test]
+    fn fixed_price() {
+        let schedule = PriceSchedule::Fixed { price: U128(7) };
+        assert_eq!(7, schedule.price_at(0, 0));
+        assert_eq!(7, schedule.price_at(u64::MAX, 10));
+        assert_eq!(21, schedule.cost_at(0, 10, 3));
+        let schedule = PriceSchedule::Fixed { price: U128(u128::MAX) };
+        assert_eq!(u128::MAX, schedule.cost_at(0, 0, 2));
+        assert_eq!(None, schedule.next_drop_at(0));
+        assert_eq!(None, schedule.rebate_floor());
+    }
+
+    #[test]
+    fn dutch_auction_price() {
+        let schedule = dutch_auction(false);
+
+        // not started yet
+        assert_eq!(100, schedule.price_at(0, 0));
+        assert_eq!(Some(1010), schedule.next_drop_at(0));
+
+        assert_eq!(100, schedule.price_at(1000, 0));
+        assert_eq!(100, schedule.price_at(1009, 0));
+        assert_eq!(Some(1010), schedule.next_drop_at(1009));
+
+        assert_eq!(75, schedule.price_at(1010, 0));
+        assert_eq!(50, schedule.price_at(1025, 0));
+        assert_eq!(Some(1030), schedule.next_drop_at(1025));
+
+        // never goes below floor
+        assert_eq!(40, schedule.price_at(1030, 0));
+        assert_eq!(40, schedule.price_at(u64::MAX, 0));
+        assert_eq!(None, schedule.next_drop_at(1030));
+    }
+
+    #[test]
+    fn dutch_auction_rebate_floor() {
+        assert_eq!(None, dutch_auction(false).rebate_floor());
+        assert_eq!(Some(40), dutch_auction(true).rebate_floor());
+    }
+
+    #[test]
+    fn linear_bonding_curve() {
+        let schedule = PriceSchedule::BondingCurve {
+            base_price: U128(100),
+            curve: Curve::Linear { increment: U128(10) },
+        };
+
+        assert_eq!(100, schedule.price_at(0, 0));
+        assert_eq!(150, schedule.price_at(0, 5));
+        // time doesn't matter
+        assert_eq!(150, schedule.price_at(u64::MAX, 5));
+        assert_eq!(None, schedule.next_drop_at(0));
+
+        // 150 + 160 + 170
+        assert_eq!(480, schedule.cost_at(0, 5, 3));
+        assert_eq!(150, schedule.cost_at(0, 5, 1));
+        assert_eq!(0, schedule.cost_at(0, 5, 0));
+        let expected: Balance = (5..105).map(|i| schedule.price_at(0, i)).sum();
+        assert_eq!(expected, schedule.cost_at(0, 5, 100));
+
+        // saturates instead of overflow
+        let schedule = PriceSchedule::BondingCurve {
+            base_price: U128(u128::MAX / 2),
+            curve: Curve::Linear { increment: U128(u128::MAX / 2) },
+        };
+        assert_eq!(u128::MAX, schedule.cost_at(0, 1, 3));
+        assert_eq!(u128::MAX, schedule.cost_at(0, 0, u64::MAX));
+    }
+
+    #[test]
+    fn curve_follows_items_left() {
+        let mut contract = setup();
+        contract.price_schedule = PriceSchedule::BondingCurve {
+            base_price: U128(100),
+            curve: Curve::Linear { increment: U128(10) },
+        };
+        assert_eq!(100, contract.unit_price());
+
+        contract.raffle.draw();
+        contract.raffle.draw();
+        assert_eq!(8, contract.raffle.items_left());
+        assert_eq!(120, contract.unit_price());
+        assert_eq!(250, contract.mint_cost_for(2));
+    }
+
+    #[test]
+    fn exponential_bonding_curve() {
+        let schedule = PriceSchedule::BondingCurve {
+            base_price: U128(1_000_000),
+            // 10% per box
+            curve: Curve::Exponential { rate: 1_000 },
+        };
+
+        assert_eq!(1_000_000, schedule.price_at(0, 0));
+        assert_eq!(1_100_000, schedule.price_at(0, 1));
+        assert_eq!(1_210_000, schedule.price_at(0, 2));
+        assert_eq!(1_331_000, schedule.price_at(0, 3));
+        assert_eq!(2_593_742, schedule.price_at(0, 10));
+
+        assert_eq!(3_641_000, schedule.cost_at(0, 1, 3));
+        assert_eq!(0, schedule.cost_at(0, 1, 0));
+
+        // a flat curve is n times the price
+        let flat = PriceSchedule::BondingCurve {
+            base_price: U128(1_000_000),
+            curve: Curve::Exponential { rate: 0 },
+        };
+        assert_eq!(3_000_000, flat.cost_at(0, 7, 3));
+
+        // saturates instead of overflow, without looping over n
+        assert!(schedule.price_at(0, u64::MAX) > 0);
+        assert!(schedule.cost_at(0, 0, u64::MAX) >= schedule.cost_at(0, 0, 1_000));
+    }
+
+    #[test]
+    fn discount_tiers() {
+        let tiers = vec![
+            DiscountTier { min_boxes: 10, discount: 1_000 },
+            DiscountTier { min_boxes: 5, discount: 500 },
+        ];
+
+        assert_eq!(0, discount_for(&tiers, 1));
+        assert_eq!(0, discount_for(&tiers, 4));
+        assert_eq!(500, discount_for(&tiers, 5));
+        assert_eq!(500, discount_for(&tiers, 9));
+        assert_eq!(1_000, discount_for(&tiers, 10));
+        assert_eq!(1_000, discount_for(&tiers, 100));
+        assert_eq!(0, discount_for(&[], 100));
+
+        assert_eq!(400, apply_discount(&tiers, 4, 400));
+        assert_eq!(475, apply_discount(&tiers, 5, 500));
+        assert_eq!(900, apply_discount(&tiers, 10, 1_000));
+    }
+}
@@ -0,0 +1,230 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    serde::{Serialize, Deserialize},
+    json_types::{Base58CryptoHash, U64},
+    near_bindgen, env, require, Balance, CryptoHash,
+};
+use crate::royalty::{PERCENTAGE_BASIS, apply_percent};
+
+/// --- PromoCode
+/// Discount codes created by the owner.
+/// Only the sha256 hash of a code is stored on chain,
+/// buyers pass the plain code to `buy` to redeem it.
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PromoCode {
+    pub discount: Percentage,
+    pub max_uses: u32,
+    pub used: u32,
+    pub expires_at: Option<U64>,
+}
+
+pub fn hash_promo_code(code: &str) -> CryptoHash {
+    env::sha256_array(code.as_bytes())
+}
+
+impl Contract {
+    /// apply promo code discount to `cost`
+    /// return the discounted cost
+    pub(crate) fn redeem_promo_code(
+        &mut self,
+        code: Option<String>,
+        account_id: &AccountId,
+        cost: Balance
+    ) -> Balance {
+        let code_hash = match code {
+            Some(code) => hash_promo_code(&code),
+            None => return cost,
+        };
+
+        let mut promo = self.valid_promo_code(&code_hash);
+        promo.used += 1;
+        self.promo_codes.insert(&code_hash, &promo);
+
+        let discount = apply_percent(cost, &promo.discount);
+
+        event::NearEvent::log_promo_code_redeem(
+            account_id.to_string(),
+            String::from(&Base58CryptoHash::from(code_hash)),
+            discount.to_string()
+        );
+
+        cost - discount
+    }
+
+    /// discounted cost for a promo code, without redeeming it,
+    /// fails the same way `redeem_promo_code` does
+    pub(crate) fn promo_code_cost(
+        &self,
+        code: &str,
+        cost: Balance
+    ) -> Balance {
+        let promo = self.valid_promo_code(&hash_promo_code(code));
+        cost - apply_percent(cost, &promo.discount)
+    }
+
+    /// the promo code if it can be redeemed now
+    fn valid_promo_code(
+        &self,
+        code_hash: &CryptoHash
+    ) -> PromoCode {
+        let promo = self.promo_codes.get(code_hash)
+            .expect(error::ERR_PROMO_CODE_INVALID);
+
+        if let Some(expires_at) = promo.expires_at {
+            require!(
+                env::block_timestamp() < expires_at.0,
+                error::ERR_PROMO_CODE_EXPIRED
+            );
+        }
+        require!(
+            promo.used < promo.max_uses,
+            error::ERR_PROMO_CODE_USED_UP
+        );
+
+        promo
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// add or replace a promo code by the sha256 hash of it
+    pub fn add_promo_code(
+        &mut self,
+        code_hash: Base58CryptoHash,
+        discount: Percentage,
+        max_uses: u32,
+        expires_at: Option<U64>,
+    ) {
+        self.assert_role(Role::Admin);
+        require!(
+            discount <= PERCENTAGE_BASIS,
+            error::ERR_PROMO_CODE_BAD_DISCOUNT
+        );
+
+        self.promo_codes.insert(
+            &code_hash.into(),
+            &PromoCode {
+                discount,
+                max_uses,
+                used: 0,
+                expires_at,
+            }
+        );
+    }
+
+    pub fn remove_promo_code(
+        &mut self,
+        code_hash: Base58CryptoHash,
+    ) {
+        self.assert_role(Role::Admin);
+        self.promo_codes.remove(&code_hash.into());
+    }
+
+    pub fn promo_code(
+        &self,
+        code_hash: Base58CryptoHash,
+    ) -> Option<PromoCode> {
+        self.promo_codes.get(&code_hash.into())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{context, setup, set_predecessor};
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+
+    fn set_time(timestamp: u64) {
+        testing_env!(context(accounts(1))
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    /// "hello" for 10% off, 2 uses, until 1000
+    fn setup_promo() -> Contract {
+        let mut contract = setup();
+        set_predecessor(accounts(0));
+        contract.add_promo_code(
+            hash_promo_code("hello").into(),
+            1_000,
+            2,
+            Some(U64(1000))
+        );
+        contract
+    }
+
+    #[test]
+    fn hash() {
+        // echo -n "hello" | sha256sum
+        assert_eq!(
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+            hash_promo_code("hello").iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        );
+    }
+
+    #[test]
+    fn redeem() {
+        let mut contract = setup_promo();
+        set_time(0);
+
+        assert_eq!(900, contract.promo_code_cost("hello", 1_000));
+        assert_eq!(
+            900,
+            contract.redeem_promo_code(Some("hello".to_string()), &accounts(1), 1_000)
+        );
+        assert!(get_logs().last().unwrap().contains(r#""event":"promo_code_redeem""#));
+        assert_eq!(1, contract.promo_code(hash_promo_code("hello").into()).unwrap().used);
+
+        // no code, no discount
+        assert_eq!(1_000, contract.redeem_promo_code(None, &accounts(1), 1_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "E17: promo code expired.")]
+    fn redeem_expired() {
+        let mut contract = setup_promo();
+        set_time(1000);
+        contract.redeem_promo_code(Some("hello".to_string()), &accounts(1), 1_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "E18: promo code used up.")]
+    fn redeem_used_up() {
+        let mut contract = setup_promo();
+        set_time(0);
+        for _ in 0..3 {
+            contract.redeem_promo_code(Some("hello".to_string()), &accounts(1), 1_000);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "E16: invalid promo code.")]
+    fn redeem_unknown() {
+        let mut contract = setup_promo();
+        set_time(0);
+        contract.redeem_promo_code(Some("bye".to_string()), &accounts(1), 1_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "E16: invalid promo code.")]
+    fn quote_unknown() {
+        let contract = setup_promo();
+        set_time(0);
+        contract.quote(1, Some("bye".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "E17: promo code expired.")]
+    fn quote_expired() {
+        let contract = setup_promo();
+        set_time(1000);
+        contract.quote(1, Some("hello".to_string()));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    serde::{Serialize, Deserialize},
+    collections::Vector,
+    json_types::{U128, U64},
+    near_bindgen, env, Balance,
+};
+use crate::sale::SalePhase;
+
+/// --- Purchase
+/// Every `buy` is recorded in a ledger, so that what was paid
+/// for a token can be looked up without an indexer.
+/// `price` is the amount charged for the boxes, excluding storage.
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Currency {
+    Near,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Purchase {
+    pub id: u64,
+    pub buyer_id: AccountId,
+    pub token_ids: Vec<TokenId>,
+    pub price: U128,
+    pub timestamp: U64,
+    pub phase: SalePhase,
+    pub currency: Currency,
+}
+
+impl Contract {
+    pub(crate) fn record_purchase(
+        &mut self,
+        buyer_id: &AccountId,
+        tokens: &[Token],
+        price: Balance,
+        phase: SalePhase
+    ) {
+        let id = self.purchases.len();
+        let token_ids: Vec<TokenId> = tokens.iter()
+            .map(|token| token.token_id.clone())
+            .collect();
+
+        for token_id in token_ids.iter() {
+            self.purchase_by_token.insert(token_id, &id);
+        }
+
+        self.purchases.push(&Purchase {
+            id,
+            buyer_id: buyer_id.clone(),
+            token_ids,
+            price: price.into(),
+            timestamp: env::block_timestamp().into(),
+            phase,
+            currency: Currency::Near,
+        });
+
+        let mut account_purchases = self.purchases_per_account.get(buyer_id)
+            .unwrap_or_else(|| {
+                self.unique_buyers += 1;
+                Vector::new(StorageKey::PurchasesPerAccountInner {
+                    account_hash: env::sha256(buyer_id.as_bytes()),
+                })
+            });
+        account_purchases.push(&id);
+        self.purchases_per_account.insert(buyer_id, &account_purchases);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn purchase(
+        &self,
+        id: u64
+    ) -> Option<Purchase> {
+        self.purchases.get(id)
+    }
+
+    /// the purchase in which a token was bought
+    pub fn purchase_of_token(
+        &self,
+        token_id: TokenId
+    ) -> Option<Purchase> {
+        self.purchase_by_token.get(&token_id)
+            .and_then(|id| self.purchases.get(id))
+    }
+
+    pub fn purchases_by_account(
+        &self,
+        account_id: AccountId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<Purchase> {
+        let account_purchases = match self.purchases_per_account.get(&account_id) {
+            Some(purchases) => purchases,
+            None => return vec![],
+        };
+
+        account_purchases.iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(u64::MAX) as usize)
+            .filter_map(|id| self.purchases.get(id))
+            .collect()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::escrow::{Escrow, EscrowConfig};
+    use crate::test_utils::{setup, set_predecessor};
+    use near_sdk::test_utils::accounts;
+
+    fn tokens(n: u64) -> Vec<Token> {
+        // longest possible ids
+        (0..n)
+            .map(|i| Token {
+                token_id: (u64::MAX - i).to_string(),
+                owner_id: accounts(2),
+                metadata: None,
+                approved_account_ids: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn storage_per_purchase() {
+        let mut contract = setup();
+        set_predecessor(accounts(2));
+        contract.escrow = Some(Escrow::new(EscrowConfig {
+            threshold: 5,
+            deadline: U64(u64::MAX),
+        }));
+
+        for n in [1, 100] {
+            let tokens = tokens(n);
+            let buyer_id = format!("buyer-{}.near", n).parse().unwrap();
+
+            let init_storage_usage = env::storage_usage();
+            contract.record_purchase(&buyer_id, &tokens, 1_000, SalePhase::Escrow);
+            contract.hold_in_escrow(&tokens, 1_000, 100);
+            let used = env::storage_usage() - init_storage_usage;
+
+            assert!(used <= STORAGE_PER_PURCHASE + n * STORAGE_PER_PURCHASED_TOKEN);
+        }
+
+        let purchase = contract.purchase_of_token(u64::MAX.to_string()).unwrap();
+        assert_eq!(SalePhase::Escrow, purchase.phase);
+    }
+}
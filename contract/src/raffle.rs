@@ -8,10 +8,16 @@ use crate::error::*;
 use crate::util::get_random_number;
 
 
+/// `items` maps a position in the pool to the item there,
+/// a position not in `items` holds the item of same value.
+/// `positions` is the reverse of `items`,
+/// so that a specific item can be found in O(1).
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct Raffle {
+    total: u64,
     len: u64,
     items: LookupMap<u64, u64>,
+    positions: LookupMap<u64, u64>,
 }
 
 impl Raffle {
@@ -22,23 +28,83 @@ impl Raffle {
     where
         S: IntoStorageKey,
     {
+        let prefix = items_key_prefix.into_storage_key();
+
         Self {
+            total: len,
             len: len,
-            items: LookupMap::new(items_key_prefix)
+            items: LookupMap::new(prefix.clone()),
+            positions: LookupMap::new([prefix, b"p".to_vec()].concat()),
         }
     }
 
+    /// restore a raffle from its storage prefix, with
+    /// `len` items left out of `total`.
+    /// items swapped around before `positions` existed
+    /// can't be taken, they can still be drawn.
+    pub fn resume<S>(
+        items_key_prefix: S,
+        total: u64,
+        len: u64,
+    ) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let mut raffle = Self::new(items_key_prefix, total);
+        raffle.len = len;
+        raffle
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
     pub fn items_left(&self) -> u64 {
         self.len
     }
 
+    pub fn items_drawn(&self) -> u64 {
+        self.total - self.len
+    }
+
     pub fn draw(&mut self) -> u64 {
         require!(self.len > 0, ERR_NO_ENOUGH_ITEMS);
 
         let i = get_random_number(self.len);
+
+        self.remove_at(i)
+    }
+
+    /// remove a specific item from the pool
+    pub fn take(&mut self, item: u64) -> u64 {
+        let i = self.position_of(item);
+        require!(
+            i < self.len && self.get_item(i) == item,
+            ERR_ITEM_NOT_AVAILABLE
+        );
+
+        self.remove_at(i)
+    }
+
+    /// put a drawn item back at the end of the pool
+    pub fn insert(&mut self, item: u64) {
+        let i = self.position_of(item);
+        require!(
+            item < self.total && !(i < self.len && self.get_item(i) == item),
+            ERR_ITEM_NOT_DRAWN
+        );
+
+        self.set_item(self.len, item);
+        self.len += 1;
+    }
+
+    /// swap the item at position `i` with the last one,
+    /// then remove it
+    fn remove_at(&mut self, i: u64) -> u64 {
         let result = self.get_item(i);
 
         self.set_item(i, self.get_item(self.len - 1));
+        self.positions.remove(&result);
 
         self.len -= 1;
 
@@ -47,11 +113,16 @@ impl Raffle {
 
     fn set_item(&mut self, key: u64, value: u64) {
         self.items.insert(&key, &value);
+        self.positions.insert(&value, &key);
     }
 
     fn get_item(&self, key: u64) -> u64 {
         self.items.get(&key).unwrap_or(key)
     }
+
+    fn position_of(&self, item: u64) -> u64 {
+        self.positions.get(&item).unwrap_or(item)
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -59,6 +130,124 @@ impl Raffle {
 mod tests {
     use super::Raffle;
 
+    /// draw everything left, and check that together with
+    /// `taken` all items are drawn exactly once
+    fn assert_permutation(raffle: &mut Raffle, taken: Vec<u64>, n: u64) {
+        let mut results = taken;
+        while raffle.items_left() > 0 {
+            results.push(raffle.draw());
+        }
+
+        results.sort_unstable();
+        assert_eq!((0..n).collect::<Vec<u64>>(), results);
+    }
+
+    #[test]
+    fn take() {
+        const N: u64 = 20;
+        let mut raffle = Raffle::new(b"t".to_vec(), N);
+
+        assert_eq!(0, raffle.take(0));
+        assert_eq!(19, raffle.take(19));
+        assert_eq!(N - 2, raffle.items_left());
+        assert_eq!(2, raffle.items_drawn());
+
+        assert_permutation(&mut raffle, vec![0, 19], N);
+    }
+
+    #[test]
+    fn take_after_draw() {
+        for k in 0..10 {
+            const N: u64 = 20;
+            let mut raffle = Raffle::new(vec![b'd', k], N);
+
+            let mut taken = Vec::new();
+            for _ in 0..5 {
+                taken.push(raffle.draw());
+            }
+            // take items which may have been swapped around
+            for item in (0..N).rev() {
+                if !taken.contains(&item) && item % 3 == 0 {
+                    assert_eq!(item, raffle.take(item));
+                    taken.push(item);
+                }
+            }
+
+            assert_permutation(&mut raffle, taken, N);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "E22: item not available.")]
+    fn take_twice() {
+        let mut raffle = Raffle::new(b"x".to_vec(), 5);
+        raffle.take(3);
+        raffle.take(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "E22: item not available.")]
+    fn take_out_of_range() {
+        let mut raffle = Raffle::new(b"y".to_vec(), 5);
+        raffle.take(5);
+    }
+
+    #[test]
+    fn insert() {
+        for k in 0..10 {
+            const N: u64 = 20;
+            let mut raffle = Raffle::new(vec![b'i', k], N);
+
+            let mut taken = Vec::new();
+            for _ in 0..8 {
+                taken.push(raffle.draw());
+            }
+            // put some back, then draw and take them around
+            for item in taken.drain(..4).collect::<Vec<u64>>() {
+                raffle.insert(item);
+            }
+            assert_eq!(N - 4, raffle.items_left());
+            assert_eq!(4, raffle.items_drawn());
+
+            taken.push(raffle.draw());
+            let item = taken.remove(0);
+            raffle.insert(item);
+            assert_eq!(item, raffle.take(item));
+            taken.push(item);
+
+            assert_permutation(&mut raffle, taken, N);
+        }
+    }
+
+    #[test]
+    fn insert_all() {
+        const N: u64 = 10;
+        let mut raffle = Raffle::new(b"a".to_vec(), N);
+        let drawn: Vec<u64> = (0..N).map(|_| raffle.draw()).collect();
+        for item in drawn {
+            raffle.insert(item);
+        }
+        assert_eq!(N, raffle.items_left());
+
+        assert_permutation(&mut raffle, vec![], N);
+    }
+
+    #[test]
+    #[should_panic(expected = "E53: item not drawn.")]
+    fn insert_not_drawn() {
+        let mut raffle = Raffle::new(b"n".to_vec(), 5);
+        raffle.take(1);
+        raffle.insert(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "E53: item not drawn.")]
+    fn insert_out_of_range() {
+        let mut raffle = Raffle::new(b"o".to_vec(), 5);
+        raffle.draw();
+        raffle.insert(5);
+    }
+
     #[test]
     pub fn test() {
         // run test_raffle for many times to make sure it's correct
@@ -78,6 +267,7 @@ mod tests {
 
         // raffle should be empty
         assert_eq!(0, raffle.items_left());
+        assert_eq!(N, raffle.items_drawn());
 
         // check if all number are in results
         for i in 0..N {
@@ -0,0 +1,203 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    json_types::U128,
+    near_bindgen, env, Balance, Promise,
+};
+
+/// --- Rebate
+/// In a dutch auction with rebate, only the floor price of each box
+/// is counted as income when it's sold. The rest is held until the
+/// auction clears, then each buyer can claim back what they paid
+/// above the clearing price, and the remaining part becomes income.
+
+#[derive(BorshSerialize, BorshDeserialize, Default)]
+pub struct RebateRecord {
+    boxes: u64,
+    paid: Balance,
+    held: Balance,
+}
+
+impl RebateRecord {
+    fn rebate_at(
+        &self,
+        clearing_price: Balance
+    ) -> Balance {
+        let clearing_cost = Balance::from(self.boxes) * clearing_price;
+        self.paid.saturating_sub(clearing_cost).min(self.held)
+    }
+}
+
+impl Contract {
+    /// record a purchase for rebate
+    /// return the part of `paid` that can be counted as income now
+    pub(crate) fn hold_for_rebate(
+        &mut self,
+        buyer_id: &AccountId,
+        n: u64,
+        paid: Balance
+    ) -> Balance {
+        let floor_price = match self.price_schedule.rebate_floor() {
+            Some(price) => price,
+            None => return paid,
+        };
+
+        let held = paid.saturating_sub(Balance::from(n) * floor_price);
+
+        let mut record = self.rebates.get(buyer_id).unwrap_or_default();
+        record.boxes += n;
+        record.paid += paid;
+        record.held += held;
+        self.rebates.insert(buyer_id, &record);
+
+        self.rebate_reserve += held;
+
+        paid - held
+    }
+
+    /// the auction clears when everything is sold,
+    /// or when the price reaches floor
+    pub(crate) fn clearing_price(
+        &self
+    ) -> Option<Balance> {
+        let floor_price = self.price_schedule.rebate_floor()?;
+
+        if self.items_for_sale() == 0 {
+            return Some(self.last_sale_price);
+        }
+
+        let now = env::block_timestamp();
+        if self.price_schedule.price_at(now, 0) <= floor_price {
+            return Some(floor_price);
+        }
+
+        None
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// claimable rebate of an account,
+    /// none if the auction has not cleared yet
+    pub fn rebate_of(
+        &self,
+        account_id: AccountId
+    ) -> Option<U128> {
+        let clearing_price = self.clearing_price()?;
+
+        let rebate = self.rebates.get(&account_id)
+            .map_or(0, |record| record.rebate_at(clearing_price));
+
+        Some(rebate.into())
+    }
+
+    /// claim back the amount paid above the clearing price
+    pub fn claim_rebate(
+        &mut self
+    ) -> U128 {
+        let clearing_price = self.clearing_price()
+            .expect(error::ERR_AUCTION_NOT_CLEARED);
+
+        let account_id = env::predecessor_account_id();
+        let record = self.rebates.remove(&account_id)
+            .expect(error::ERR_NO_REBATE);
+
+        let rebate = record.rebate_at(clearing_price);
+
+        self.rebate_reserve -= record.held;
+        self.total_income += record.held - rebate;
+
+        if rebate > 0 {
+            Promise::new(account_id).transfer(rebate);
+        }
+
+        rebate.into()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pricing::PriceSchedule;
+    use crate::test_utils::{context, setup};
+    use near_sdk::json_types::U64;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    fn set_time(predecessor: AccountId, timestamp: u64) {
+        testing_env!(context(predecessor)
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    /// dutch auction from 100 down to 40, by 20 every 10ns from 1000
+    fn setup_auction() -> Contract {
+        let mut contract = setup();
+        contract.price_schedule = PriceSchedule::DutchAuction {
+            start_price: U128(100),
+            floor_price: U128(40),
+            decrement: U128(20),
+            interval: U64(10),
+            start_at: U64(1000),
+            rebate: true,
+        };
+        contract
+    }
+
+    #[test]
+    fn rebate_amount() {
+        // 2 boxes bought at 90, floor is 40
+        let record = RebateRecord { boxes: 2, paid: 180, held: 100 };
+        assert_eq!(100, record.rebate_at(40));
+        assert_eq!(60, record.rebate_at(60));
+        assert_eq!(0, record.rebate_at(90));
+        assert_eq!(0, record.rebate_at(100));
+
+        // never more than what's held
+        let record = RebateRecord { boxes: 2, paid: 180, held: 50 };
+        assert_eq!(50, record.rebate_at(40));
+    }
+
+    #[test]
+    fn hold_and_claim() {
+        let mut contract = setup_auction();
+
+        // 2 boxes at 100, only the floor counts as income
+        set_time(accounts(2), 0);
+        assert_eq!(80, contract.hold_for_rebate(&accounts(2), 2, 200));
+        assert_eq!(120, contract.rebate_reserve);
+        assert_eq!(None, contract.clearing_price());
+        assert_eq!(None, contract.rebate_of(accounts(2)));
+
+        // price reaches floor
+        set_time(accounts(2), 1030);
+        assert_eq!(Some(40), contract.clearing_price());
+        assert_eq!(Some(U128(120)), contract.rebate_of(accounts(2)));
+
+        assert_eq!(U128(120), contract.claim_rebate());
+        assert_eq!(0, contract.rebate_reserve);
+        assert_eq!(0, contract.total_income);
+        assert_eq!(Some(U128(0)), contract.rebate_of(accounts(2)));
+    }
+
+    #[test]
+    fn clearing_price_when_sold_out() {
+        let mut contract = setup_auction();
+
+        set_time(accounts(2), 1010);
+        assert_eq!(80, contract.hold_for_rebate(&accounts(2), 2, 200));
+        while contract.raffle.items_left() > 0 {
+            contract.raffle.draw();
+        }
+        contract.last_sale_price = 80;
+
+        // sold out above floor, clears at the last sale price
+        assert_eq!(Some(80), contract.clearing_price());
+        assert_eq!(Some(U128(40)), contract.rebate_of(accounts(2)));
+
+        assert_eq!(U128(40), contract.claim_rebate());
+        assert_eq!(0, contract.rebate_reserve);
+        assert_eq!(80, contract.total_income);
+    }
+}
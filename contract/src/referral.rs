@@ -0,0 +1,158 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    serde::{Serialize, Deserialize},
+    json_types::U128,
+    near_bindgen, env, require, Balance, Promise,
+};
+use crate::royalty::{PERCENTAGE_BASIS, apply_percent};
+
+/// --- Referral
+/// A buyer can name a referrer when buying boxes.
+/// The referrer earns `rate` (in PERCENTAGE_BASIS) of the purchase,
+/// which is taken out of the income before it's distributed
+/// through royalties, and can be claimed later.
+/// `cap` limits the total reward a single referrer can earn.
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReferralConfig {
+    pub rate: Percentage,
+    pub cap: Option<U128>,
+}
+
+impl ReferralConfig {
+    pub fn assert_valid(&self) {
+        require!(
+            self.rate <= PERCENTAGE_BASIS,
+            error::ERR_BAD_REFERRAL_CONFIG
+        );
+    }
+
+    /// reward for a purchase of `amount`,
+    /// given the referrer has already `earned` some
+    fn reward_for(
+        &self,
+        amount: Balance,
+        earned: Balance
+    ) -> Balance {
+        let reward = apply_percent(amount, &self.rate);
+
+        self.cap.map_or(reward, |cap| {
+            reward.min(cap.0.saturating_sub(earned))
+        })
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReferralStats {
+    pub purchases: u64,
+    pub boxes: u64,
+    pub earned: U128,
+    pub claimable: U128,
+}
+
+impl Default for ReferralStats {
+    fn default() -> Self {
+        Self {
+            purchases: 0,
+            boxes: 0,
+            earned: U128(0),
+            claimable: U128(0),
+        }
+    }
+}
+
+impl Contract {
+    /// credit referral reward for a purchase
+    /// return the reward which should be taken out of income
+    pub(crate) fn reward_referrer(
+        &mut self,
+        referrer_id: Option<AccountId>,
+        buyer_id: &AccountId,
+        n: u64,
+        amount: Balance
+    ) -> Balance {
+        let (config, referrer_id) = match (&self.referral, referrer_id) {
+            (Some(config), Some(referrer_id)) => (config, referrer_id),
+            _ => return 0,
+        };
+
+        require!(
+            referrer_id != *buyer_id,
+            error::ERR_SELF_REFERRAL
+        );
+
+        let mut stats = self.referrals.get(&referrer_id).unwrap_or_default();
+        let reward = config.reward_for(amount, stats.earned.0);
+
+        stats.purchases += 1;
+        stats.boxes += n;
+        stats.earned = (stats.earned.0 + reward).into();
+        stats.claimable = (stats.claimable.0 + reward).into();
+        self.referrals.insert(&referrer_id, &stats);
+
+        reward
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn referral_config(
+        &self
+    ) -> Option<ReferralConfig> {
+        self.referral.clone()
+    }
+
+    pub fn referral_stats(
+        &self,
+        account_id: AccountId
+    ) -> Option<ReferralStats> {
+        self.referrals.get(&account_id)
+    }
+
+    /// claim all referral rewards of caller
+    pub fn claim_referral_rewards(
+        &mut self
+    ) -> U128 {
+        self.assert_escrow_released();
+
+        let account_id = env::predecessor_account_id();
+        let mut stats = self.referrals.get(&account_id)
+            .expect(error::ERR_NO_REFERRAL_REWARD);
+
+        let amount = stats.claimable.0;
+        require!(amount > 0, error::ERR_NO_REFERRAL_REWARD);
+
+        stats.claimable = 0.into();
+        self.referrals.insert(&account_id, &stats);
+
+        Promise::new(account_id).transfer(amount);
+
+        amount.into()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reward() {
+        let config = ReferralConfig { rate: 500, cap: None };
+        assert_eq!(50, config.reward_for(1_000, 0));
+        assert_eq!(50, config.reward_for(1_000, 1_000_000));
+    }
+
+    #[test]
+    fn reward_with_cap() {
+        let config = ReferralConfig { rate: 500, cap: Some(U128(120)) };
+        assert_eq!(50, config.reward_for(1_000, 0));
+        assert_eq!(50, config.reward_for(1_000, 70));
+        assert_eq!(20, config.reward_for(1_000, 100));
+        assert_eq!(0, config.reward_for(1_000, 120));
+        assert_eq!(0, config.reward_for(1_000, 200));
+    }
+}
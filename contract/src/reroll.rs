@@ -0,0 +1,247 @@
+use crate::*;
+use near_sdk::{
+    json_types::U128,
+    near_bindgen, env, require, assert_one_yocto,
+};
+use near_contract_standards::non_fungible_token::Token;
+
+/// --- Reroll
+/// Holders can trade an unwanted item in for a new draw, for a fee
+/// set by admin. The token is burned and its item put back into the
+/// raffle before drawing, so the same item may come out again.
+/// The fee counts as primary income. The purchase of the old token is
+/// looked up by the new one, and in escrow mode what was paid for the
+/// old token can be refunded on the new one.
+/// Only bought tokens can be rerolled, not staked ones,
+/// nor anything after the sale failed.
+
+#[near_bindgen]
+impl Contract {
+    pub fn reroll_fee(
+        &self
+    ) -> Option<U128> {
+        self.reroll_fee.map(U128)
+    }
+
+    /// none disables reroll
+    #[payable]
+    pub fn set_reroll_fee(
+        &mut self,
+        fee: Option<U128>
+    ) {
+        assert_one_yocto();
+        self.assert_role(Role::Admin);
+        self.reroll_fee = fee.map(|fee| fee.0);
+    }
+
+    /// burn a token and draw a new one, the attached deposit
+    /// pays the fee and storage, the rest is refunded
+    #[payable]
+    pub fn reroll(
+        &mut self,
+        token_id: TokenId
+    ) -> Token {
+        self.assert_not_paused(PauseTarget::Buy);
+        let fee = self.reroll_fee.expect(error::ERR_REROLL_DISABLED);
+        require!(
+            self.escrow_status() != escrow::EscrowStatus::Failed,
+            error::ERR_SALE_FAILED
+        );
+
+        // a staked token is held by the contract
+        self.assert_not_staked(&token_id);
+        let owner_id = self.owner_of(&token_id)
+            .expect(error::ERR_TOKEN_NOT_EXIST);
+        require!(
+            owner_id == env::predecessor_account_id(),
+            error::ERR_NOT_TOKEN_OWNER
+        );
+
+        // only items drawn by `buy` go back into the raffle
+        let purchase_id = self.purchase_by_token.get(&token_id)
+            .expect(error::ERR_ITEM_NOT_AVAILABLE);
+        let index = util::item_index(&token_id);
+
+        let deposit = env::attached_deposit();
+        require!(
+            deposit >= fee,
+            format!(
+                "{} Require {}.",
+                error::ERR_NO_ENOUGH_DEPOSIT,
+                fee,
+            )
+        );
+
+        // burning drops the payment of the old token,
+        // it's moved to the new one
+        let payment = self.take_escrow_payment(&token_id);
+        self.internal_burn(&token_id, &owner_id);
+        event::NearEvent::log_nft_burn(
+            owner_id.to_string(),
+            vec![token_id.clone()],
+            Some("reroll".to_string()),
+            None
+        );
+
+        // storage is measured after burning,
+        // so the caller only pays for the new token
+        let init_storage_usage = env::storage_usage();
+        self.raffle.insert(index);
+        let token = self.mint_many(1, &owner_id).remove(0);
+
+        if let Some(payment) = payment {
+            self.put_escrow_payment(&token.token_id, payment);
+        }
+        self.purchase_by_token.insert(&token.token_id, &purchase_id);
+
+        self.total_income += fee;
+
+        self.refund_storage_deposit(
+            init_storage_usage,
+            deposit - fee,
+            &owner_id
+        );
+
+        token
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::escrow::{Escrow, EscrowConfig};
+    use crate::sale::SalePhase;
+    use crate::test_utils::{context, setup, mint, set_context, set_predecessor};
+    use near_sdk::json_types::U64;
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+
+    const FEE: Balance = 1_000;
+
+    /// reroll enabled, and a token bought by accounts(1)
+    fn setup_reroll() -> (Contract, Token) {
+        let mut contract = setup();
+        set_predecessor(accounts(0));
+        contract.set_reroll_fee(Some(U128(FEE)));
+
+        set_context(accounts(1), FEE + 10u128.pow(22));
+        let token = contract.mint_many(1, &accounts(1)).remove(0);
+        contract.record_purchase(&accounts(1), std::slice::from_ref(&token), 500, SalePhase::Open);
+        assert_eq!(9, contract.raffle.items_left());
+
+        (contract, token)
+    }
+
+    #[test]
+    fn reroll() {
+        let (mut contract, token) = setup_reroll();
+
+        let new_token = contract.reroll(token.token_id.clone());
+
+        assert_eq!(9, contract.raffle.items_left());
+        assert_eq!(FEE, contract.total_income);
+        // the old token is burned, even if the same item came out again
+        assert_eq!(
+            vec![new_token.token_id.clone()],
+            contract.nft_tokens_for_owner(accounts(1), None, None)
+                .into_iter()
+                .map(|token| token.token_id)
+                .collect::<Vec<TokenId>>()
+        );
+        let burn_log = format!(
+            r#""event":"nft_burn","data":[{{"owner_id":"bob","token_ids":["{}"],"memo":"reroll"}}]"#,
+            token.token_id
+        );
+        assert!(get_logs().iter().any(|log| log.contains(&burn_log)));
+        let purchase = contract.purchase_of_token(new_token.token_id.clone()).unwrap();
+        assert_eq!(500, purchase.price.0);
+
+        // every item is still drawn exactly once
+        let mut items: Vec<u64> = (0..9)
+            .map(|_| contract.raffle.draw())
+            .collect();
+        items.push(new_token.token_id.parse().unwrap());
+        items.sort_unstable();
+        assert_eq!((0..10).collect::<Vec<u64>>(), items);
+    }
+
+    #[test]
+    fn reroll_keeps_payment() {
+        let (mut contract, token) = setup_reroll();
+        contract.escrow = Some(Escrow::new(EscrowConfig { threshold: 5, deadline: U64(1000) }));
+        contract.total_income += 500;
+        contract.hold_in_escrow(std::slice::from_ref(&token), 500, 0);
+
+        let new_token = contract.reroll(token.token_id.clone());
+
+        assert_eq!(Some(U128(500)), contract.refund_of(new_token.token_id));
+        // still held for the refund
+        set_predecessor(accounts(0));
+        contract.cancel_sale();
+        assert_eq!(500, contract.income_on_hold());
+    }
+
+    #[test]
+    #[should_panic(expected = "E64: reroll is disabled.")]
+    fn reroll_disabled() {
+        let (mut contract, token) = setup_reroll();
+        set_predecessor(accounts(0));
+        contract.set_reroll_fee(None);
+
+        set_context(accounts(1), FEE);
+        contract.reroll(token.token_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "E37: not token owner.")]
+    fn reroll_not_owner() {
+        let (mut contract, token) = setup_reroll();
+
+        set_context(accounts(2), FEE);
+        contract.reroll(token.token_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "E58: token is staked.")]
+    fn reroll_staked() {
+        let (mut contract, token) = setup_reroll();
+        contract.staked_by.insert(&token.token_id, &accounts(1));
+
+        set_context(accounts(1), FEE);
+        contract.reroll(token.token_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "E03: no enough deposit. Require 1000.")]
+    fn reroll_deposit_below_fee() {
+        let (mut contract, token) = setup_reroll();
+
+        set_context(accounts(1), FEE - 1);
+        contract.reroll(token.token_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "E22: item not available.")]
+    fn reroll_not_bought() {
+        let (mut contract, _) = setup_reroll();
+        // minted by owner, or out of the fusion pool
+        mint(&mut contract, "10", accounts(1));
+
+        set_context(accounts(1), FEE);
+        contract.reroll("10".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "E34: sale failed.")]
+    fn reroll_after_sale_failed() {
+        let (mut contract, token) = setup_reroll();
+        contract.escrow = Some(Escrow::new(EscrowConfig { threshold: 5, deadline: U64(1000) }));
+
+        testing_env!(context(accounts(1))
+            .attached_deposit(FEE)
+            .block_timestamp(1000)
+            .build());
+        contract.reroll(token.token_id);
+    }
+}
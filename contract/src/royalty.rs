@@ -46,6 +46,12 @@ impl Royalty {
         &self.royalties
     }
 
+    pub fn get_royalty_rate(
+        &self
+    ) -> Percentage {
+        self.royalty_rate
+    }
+
     fn assert_valid_royalties(
         royalties: &RoyaltyMap,
         royalty_rate: Percentage,
@@ -118,7 +124,7 @@ impl Payout {
     }
 }
 
-fn apply_percent(
+pub(crate) fn apply_percent(
     amount: Balance,
     percent: &Percentage
 ) -> Balance {
@@ -189,16 +195,19 @@ impl NFTPayouts for Contract {
         max_len_payout: Option<u32>,
     ) -> Payout {
         assert_one_yocto();
+        self.assert_not_paused(PauseTarget::Transfer);
+
         let payouts = self.nft_payout(
             token_id.clone(),
             balance,
             max_len_payout
         );
-        self.nft_transfer(
-            receiver_id.clone(),
-            token_id.clone(),
-            approval_id.clone(),
-            memo.clone()
+        self.internal_transfer(
+            &env::predecessor_account_id(),
+            &receiver_id,
+            &token_id,
+            approval_id,
+            memo
         );
         return payouts;
     }
@@ -0,0 +1,160 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    serde::{Serialize, Deserialize},
+    json_types::U128,
+    near_bindgen, env,
+};
+use crate::escrow::EscrowStatus;
+
+/// --- SalePhase
+/// Where the primary sale is at, derived from the current state.
+/// `Auction` while a dutch auction price is still dropping,
+/// `Escrow` while income is held waiting for the sale threshold.
+/// The last logged phase is kept, so a change caused by time passing,
+/// e.g. the price reaching floor or the escrow deadline, is logged
+/// by the first `buy`, `pause`, `unpause` or `cancel_sale` after it.
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum SalePhase {
+    Auction,
+    Escrow,
+    Open,
+    Paused,
+    SoldOut,
+    Failed,
+}
+
+impl SalePhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SalePhase::Auction => "auction",
+            SalePhase::Escrow => "escrow",
+            SalePhase::Open => "open",
+            SalePhase::Paused => "paused",
+            SalePhase::SoldOut => "sold_out",
+            SalePhase::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleStats {
+    pub total_supply: u64,
+    pub minted: u64,
+    pub remaining: u64,
+    pub total_income: U128,
+    pub distributed_income: U128,
+    pub undistributed_income: U128,
+    pub unique_buyers: u64,
+    pub current_price: U128,
+    pub phase: SalePhase,
+}
+
+impl Contract {
+    /// log phase_changed if the phase is no longer the last logged one
+    pub(crate) fn update_phase(
+        &mut self
+    ) {
+        let new_phase = self.sale_phase();
+        if new_phase != self.logged_phase {
+            event::NearEvent::log_phase_changed(
+                self.logged_phase.as_str().to_string(),
+                new_phase.as_str().to_string()
+            );
+            self.logged_phase = new_phase;
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// summary of the primary sale
+    pub fn sale_stats(
+        &self
+    ) -> SaleStats {
+        SaleStats {
+            total_supply: self.raffle.total(),
+            minted: self.raffle.items_drawn(),
+            remaining: self.raffle.items_left(),
+            total_income: self.total_income.into(),
+            distributed_income: self.distributed_income.into(),
+            undistributed_income: (self.total_income - self.distributed_income).into(),
+            unique_buyers: self.unique_buyers,
+            current_price: self.unit_price().into(),
+            phase: self.sale_phase(),
+        }
+    }
+
+    pub fn sale_phase(
+        &self
+    ) -> SalePhase {
+        let escrow_status = self.escrow_status();
+
+        if escrow_status == EscrowStatus::Failed {
+            SalePhase::Failed
+        } else if self.items_for_sale() == 0 {
+            SalePhase::SoldOut
+        } else if self.pause_state.buy {
+            SalePhase::Paused
+        } else if self.price_schedule.next_drop_at(env::block_timestamp()).is_some() {
+            SalePhase::Auction
+        } else if escrow_status == EscrowStatus::Pending {
+            SalePhase::Escrow
+        } else {
+            SalePhase::Open
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pricing::PriceSchedule;
+    use crate::test_utils::{context, setup};
+    use near_sdk::json_types::U64;
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+
+    /// a call from the owner with 1 yocto attached
+    fn set_time(timestamp: u64) {
+        testing_env!(context(accounts(0))
+            .attached_deposit(1)
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    #[test]
+    fn phase_change_by_time() {
+        let mut contract = setup();
+        set_time(0);
+        contract.price_schedule = PriceSchedule::DutchAuction {
+            start_price: U128(100),
+            floor_price: U128(40),
+            decrement: U128(20),
+            interval: U64(10),
+            start_at: U64(1000),
+            rebate: false,
+        };
+        contract.update_phase();
+        assert_eq!(SalePhase::Auction, contract.logged_phase);
+
+        // price reaches floor, logged by the next call
+        set_time(1030);
+        assert_eq!(SalePhase::Open, contract.sale_phase());
+        contract.unpause(vec![]);
+        assert_eq!(
+            get_logs(),
+            vec![r#"EVENT_JSON:{"standard":"mystery_box","version":"1.0.0","event":"phase_changed","data":[{"old_phase":"auction","new_phase":"open"}]}"#]
+        );
+
+        // only once
+        set_time(1040);
+        contract.unpause(vec![]);
+        assert!(get_logs().is_empty());
+    }
+}
@@ -0,0 +1,397 @@
+use crate::*;
+use near_sdk::{
+    borsh::{self, BorshSerialize, BorshDeserialize},
+    serde::{Serialize, Deserialize},
+    json_types::U128,
+    near_bindgen, env, require, assert_one_yocto, Balance, BlockHeight,
+};
+
+/// --- Staking
+/// Holders can stake tokens into this contract to earn points.
+/// A staked token is held by the contract until it's unstaked.
+/// Each token earns `points_per_block`, multiplied by the
+/// `multiplier` of the rarity tier its item index falls in.
+/// The rate is fixed when the token is staked.
+/// Points can be spent on `buy`, each worth `point_value` yoctoNEAR.
+/// Disabling staking stops new stakes, but staked tokens
+/// keep earning and can always be unstaked, and points can
+/// still be spent at the last `point_value`.
+/// Staked tokens can't be moved or burned by any other path.
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RarityTier {
+    pub from_index: u64,
+    pub to_index: u64,
+    pub multiplier: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StakingConfig {
+    pub points_per_block: U128,
+    pub point_value: U128,
+    pub tiers: Vec<RarityTier>,
+}
+
+impl StakingConfig {
+    pub fn assert_valid(&self) {
+        require!(self.point_value.0 > 0, error::ERR_BAD_STAKING_CONFIG);
+        for tier in self.tiers.iter() {
+            require!(
+                tier.from_index < tier.to_index && tier.multiplier > 0,
+                error::ERR_BAD_STAKING_CONFIG
+            );
+        }
+    }
+
+    /// points per block of an item, items out of
+    /// any tier earn the base rate
+    pub fn rate_of(
+        &self,
+        index: u64
+    ) -> Balance {
+        let multiplier = self.tiers.iter()
+            .find(|tier| tier.from_index <= index && index < tier.to_index)
+            .map_or(1, |tier| tier.multiplier);
+
+        self.points_per_block.0 * Balance::from(multiplier)
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Staker {
+    token_ids: Vec<TokenId>,
+    rates: Vec<Balance>,
+    points: Balance,
+    last_block: BlockHeight,
+}
+
+impl Staker {
+    fn new() -> Self {
+        Self {
+            token_ids: vec![],
+            rates: vec![],
+            points: 0,
+            last_block: env::block_height(),
+        }
+    }
+
+    fn points_at(
+        &self,
+        block: BlockHeight
+    ) -> Balance {
+        let rate: Balance = self.rates.iter().sum();
+        let blocks = Balance::from(block.saturating_sub(self.last_block));
+
+        self.points + rate * blocks
+    }
+
+    fn settle(&mut self) {
+        let block = env::block_height();
+        self.points = self.points_at(block);
+        self.last_block = block;
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_not_staked(
+        &self,
+        token_id: &TokenId
+    ) {
+        require!(
+            self.staked_by.get(token_id).is_none(),
+            error::ERR_TOKEN_STAKED
+        );
+    }
+
+    /// spend up to `points` of buyer for a discount on `mint_cost`,
+    /// only as many points as needed are spent
+    /// return mint cost after discount
+    pub(crate) fn spend_points(
+        &mut self,
+        points: Option<U128>,
+        buyer_id: &AccountId,
+        mint_cost: Balance
+    ) -> Balance {
+        let points = match points {
+            Some(points) if points.0 > 0 => points.0,
+            _ => return mint_cost,
+        };
+
+        // kept after staking is disabled
+        let point_value = self.point_value;
+        require!(point_value > 0, error::ERR_STAKING_DISABLED);
+
+        let mut staker = self.stakers.get(buyer_id)
+            .expect(error::ERR_NO_ENOUGH_POINTS);
+        staker.settle();
+        require!(staker.points >= points, error::ERR_NO_ENOUGH_POINTS);
+
+        let points_needed = mint_cost.div_ceil(point_value);
+        let spent = points.min(points_needed);
+        let discount = (spent * point_value).min(mint_cost);
+
+        staker.points -= spent;
+        self.stakers.insert(buyer_id, &staker);
+
+        event::NearEvent::log_points_spend(
+            buyer_id.to_string(),
+            spent.to_string(),
+            discount.to_string()
+        );
+
+        mint_cost - discount
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn staking_config(
+        &self
+    ) -> Option<StakingConfig> {
+        self.staking.clone()
+    }
+
+    /// none disables staking
+    #[payable]
+    pub fn set_staking_config(
+        &mut self,
+        config: Option<StakingConfig>
+    ) {
+        assert_one_yocto();
+        self.assert_role(Role::Admin);
+        if let Some(config) = config.as_ref() {
+            config.assert_valid();
+            self.point_value = config.point_value.0;
+        }
+
+        self.staking = config;
+    }
+
+    /// stake tokens into this contract,
+    /// storage is paid by the owner
+    #[payable]
+    pub fn stake(
+        &mut self,
+        token_ids: Vec<TokenId>
+    ) {
+        self.assert_not_paused(PauseTarget::Transfer);
+        let config = self.staking.clone()
+            .expect(error::ERR_STAKING_DISABLED);
+
+        let init_storage_usage = env::storage_usage();
+        let owner_id = env::predecessor_account_id();
+        let contract_id = env::current_account_id();
+
+        let mut staker = self.stakers.get(&owner_id)
+            .unwrap_or_else(Staker::new);
+        staker.settle();
+
+        for token_id in token_ids.iter() {
+            // only owner can stake
+            require!(
+                self.owner_of(token_id) == Some(owner_id.clone()),
+                error::ERR_NOT_TOKEN_OWNER
+            );
+            self.assert_transferable(token_id);
+            let index = util::item_index(token_id);
+
+            self.internal_transfer(&owner_id, &contract_id, token_id, None, None);
+            self.staked_by.insert(token_id, &owner_id);
+
+            staker.token_ids.push(token_id.clone());
+            staker.rates.push(config.rate_of(index));
+        }
+        self.stakers.insert(&owner_id, &staker);
+
+        self.refund_storage_deposit(
+            init_storage_usage,
+            env::attached_deposit(),
+            &owner_id
+        );
+
+        event::NearEvent::log_stake(owner_id.to_string(), token_ids);
+    }
+
+    /// take staked tokens back, points earned are kept
+    #[payable]
+    pub fn unstake(
+        &mut self,
+        token_ids: Vec<TokenId>
+    ) {
+        assert_one_yocto();
+
+        let owner_id = env::predecessor_account_id();
+        let contract_id = env::current_account_id();
+
+        let mut staker = self.stakers.get(&owner_id)
+            .expect(error::ERR_NOT_STAKED);
+        staker.settle();
+
+        for token_id in token_ids.iter() {
+            let i = staker.token_ids.iter()
+                .position(|id| id == token_id)
+                .expect(error::ERR_NOT_STAKED);
+            staker.token_ids.swap_remove(i);
+            staker.rates.swap_remove(i);
+
+            self.staked_by.remove(token_id);
+            self.internal_transfer(&contract_id, &owner_id, token_id, None, None);
+        }
+        self.stakers.insert(&owner_id, &staker);
+
+        event::NearEvent::log_unstake(owner_id.to_string(), token_ids);
+    }
+
+    pub fn staked_tokens(
+        &self,
+        account_id: AccountId
+    ) -> Vec<TokenId> {
+        self.stakers.get(&account_id)
+            .map_or(vec![], |staker| staker.token_ids)
+    }
+
+    /// points of an account, including the ones accrued
+    /// since it last changed
+    pub fn points_of(
+        &self,
+        account_id: AccountId
+    ) -> U128 {
+        self.stakers.get(&account_id)
+            .map_or(0, |staker| staker.points_at(env::block_height()))
+            .into()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{context, setup, setup_token, set_predecessor};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    fn set_block(block: BlockHeight) {
+        testing_env!(context(accounts(0))
+            .block_index(block)
+            .build());
+    }
+
+    /// a call with storage deposit attached at `block`
+    fn call_at(predecessor: AccountId, block: BlockHeight) {
+        testing_env!(context(predecessor)
+            .attached_deposit(10u128.pow(22))
+            .block_index(block)
+            .build());
+    }
+
+    /// token "0" of accounts(1) can be staked
+    fn setup_staking() -> Contract {
+        let mut contract = setup_token();
+        set_predecessor(accounts(0));
+        contract.set_staking_config(Some(config()));
+        contract
+    }
+
+    fn config() -> StakingConfig {
+        StakingConfig {
+            points_per_block: U128(10),
+            point_value: U128(1_000),
+            tiers: vec![
+                RarityTier { from_index: 0, to_index: 5, multiplier: 10 },
+                RarityTier { from_index: 5, to_index: 20, multiplier: 3 },
+            ],
+        }
+    }
+
+    #[test]
+    fn rate_by_tier() {
+        let config = config();
+        assert_eq!(100, config.rate_of(0));
+        assert_eq!(100, config.rate_of(4));
+        assert_eq!(30, config.rate_of(5));
+        assert_eq!(30, config.rate_of(19));
+        assert_eq!(10, config.rate_of(20));
+    }
+
+    #[test]
+    fn accrue_points() {
+        set_block(100);
+        let mut staker = Staker::new();
+        staker.rates = vec![100, 30];
+        assert_eq!(0, staker.points_at(100));
+        assert_eq!(1_300, staker.points_at(110));
+
+        set_block(110);
+        staker.settle();
+        staker.rates.pop();
+        assert_eq!(1_300, staker.points);
+        assert_eq!(3_300, staker.points_at(130));
+    }
+
+    #[test]
+    fn stake_and_unstake() {
+        let mut contract = setup_staking();
+
+        call_at(accounts(1), 100);
+        contract.stake(vec!["0".to_string()]);
+        assert_eq!(accounts(0), contract.owner_of(&"0".to_string()).unwrap());
+        assert_eq!(vec!["0"], contract.staked_tokens(accounts(1)));
+
+        call_at(accounts(1), 110);
+        assert_eq!(1_000, contract.points_of(accounts(1)).0);
+        // buying 1_500 worth spends only the 2 points needed
+        assert_eq!(0, contract.spend_points(Some(U128(5)), &accounts(1), 1_500));
+        assert_eq!(998, contract.points_of(accounts(1)).0);
+
+        set_predecessor(accounts(1));
+        contract.unstake(vec!["0".to_string()]);
+        assert_eq!(accounts(1), contract.owner_of(&"0".to_string()).unwrap());
+        assert!(contract.staked_tokens(accounts(1)).is_empty());
+    }
+
+    #[test]
+    fn spend_points_after_disabled() {
+        let mut contract = setup_staking();
+
+        call_at(accounts(1), 100);
+        contract.stake(vec!["0".to_string()]);
+
+        set_predecessor(accounts(0));
+        contract.set_staking_config(None);
+
+        call_at(accounts(1), 110);
+        assert_eq!(500, contract.spend_points(Some(U128(1)), &accounts(1), 1_500));
+        assert_eq!(999, contract.points_of(accounts(1)).0);
+    }
+
+    #[test]
+    #[should_panic(expected = "E54: staking is disabled.")]
+    fn spend_points_never_enabled() {
+        let mut contract = setup();
+
+        call_at(accounts(1), 100);
+        contract.spend_points(Some(U128(1)), &accounts(1), 1_500);
+    }
+
+    #[test]
+    #[should_panic(expected = "E27: transfer is paused.")]
+    fn stake_paused() {
+        let mut contract = setup_staking();
+        contract.pause(vec![PauseTarget::Transfer]);
+
+        call_at(accounts(1), 100);
+        contract.stake(vec!["0".to_string()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "E43: token is soulbound.")]
+    fn stake_soulbound() {
+        let mut contract = setup_staking();
+        contract.soulbound = true;
+
+        call_at(accounts(1), 100);
+        contract.stake(vec!["0".to_string()]);
+    }
+}
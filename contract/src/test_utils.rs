@@ -0,0 +1,83 @@
+use crate::*;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::testing_env;
+use near_contract_standards::non_fungible_token::metadata::{
+    NFT_METADATA_SPEC, TokenMetadata,
+};
+
+/// context of a call from `predecessor` to the contract
+/// deployed at accounts(0)
+pub(crate) fn context(predecessor: AccountId) -> VMContextBuilder {
+    let mut builder = VMContextBuilder::new();
+    builder
+        .current_account_id(accounts(0))
+        .predecessor_account_id(predecessor);
+    builder
+}
+
+/// a call from `predecessor` with 1 yocto attached
+pub(crate) fn set_predecessor(predecessor: AccountId) {
+    set_context(predecessor, 1);
+}
+
+pub(crate) fn set_context(predecessor: AccountId, deposit: Balance) {
+    testing_env!(context(predecessor)
+        .attached_deposit(deposit)
+        .build());
+}
+
+/// contract of 10 items owned by accounts(0)
+pub(crate) fn setup() -> Contract {
+    set_predecessor(accounts(0));
+    Contract::new(
+        NFTContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: "test nft".to_string(),
+            symbol: "nft".to_string(),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        },
+        10,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None
+    )
+}
+
+/// same as `setup`, with token "0" owned by accounts(1)
+pub(crate) fn setup_token() -> Contract {
+    let mut contract = setup();
+    mint(&mut contract, "0", accounts(1));
+    contract
+}
+
+/// mint a token with the given id, without drawing it
+pub(crate) fn mint(contract: &mut Contract, token_id: &str, owner_id: AccountId) {
+    contract.tokens.internal_mint_with_refund(
+        token_id.to_string(),
+        owner_id,
+        Some(TokenMetadata {
+            title: Some(token_id.to_string()),
+            description: None,
+            media: None,
+            media_hash: None,
+            extra: None,
+            reference: None,
+            reference_hash: None,
+            copies: None,
+            expires_at: None,
+            issued_at: None,
+            starts_at: None,
+            updated_at: None
+        }),
+        None
+    );
+}
@@ -0,0 +1,364 @@
+use crate::*;
+use near_sdk::{
+    near_bindgen, env, assert_one_yocto, PromiseOrValue,
+};
+use std::collections::HashMap;
+
+/// --- Transfer
+/// Every path that moves a token goes through `internal_transfer`,
+/// which logs exactly one nft_transfer event for it.
+/// A `nft_transfer_call` that gets reverted by the receiver
+/// logs a second event, for the token going back.
+/// `nft_batch_transfer` logs a single event for all its tokens.
+impl Contract {
+    /// transfer a token after checking sender is owner or approved,
+    /// return previous owner and approvals
+    pub(crate) fn internal_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) -> (AccountId, Option<HashMap<AccountId, u64>>) {
+        let (old_owner_id, old_approvals, transfer) = self.transfer_token(
+            sender_id,
+            receiver_id,
+            token_id,
+            approval_id,
+            memo
+        );
+
+        event::NearEvent::log_nft_transfers(vec![transfer]);
+
+        (old_owner_id, old_approvals)
+    }
+
+    /// logging should be handled by caller with the returned data
+    fn transfer_token(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) -> (AccountId, Option<HashMap<AccountId, u64>>, event::NftTransferData) {
+        // this is to make sure error logs are consistant
+        self.owner_of(token_id)
+            .expect(error::ERR_TOKEN_NOT_EXIST);
+        self.assert_transferable(token_id);
+
+        let (old_owner_id, old_approvals) = self.tokens.internal_transfer(
+            sender_id,
+            receiver_id,
+            token_id,
+            approval_id,
+            memo.clone()
+        );
+        self.unindex_approvals(token_id, old_approvals.as_ref());
+        self.cancel_listing(token_id);
+
+        let authorized_id = if *sender_id != old_owner_id {
+            Some(sender_id.to_string())
+        } else {
+            None
+        };
+
+        let transfer = event::NftTransferData {
+            authorized_id,
+            old_owner_id: old_owner_id.to_string(),
+            new_owner_id: receiver_id.to_string(),
+            token_ids: vec![token_id.clone()],
+            memo,
+        };
+
+        (old_owner_id, old_approvals, transfer)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// transfer many tokens in one call, each to its own receiver.
+    /// sender must own or be approved for every token
+    #[payable]
+    pub fn nft_batch_transfer(
+        &mut self,
+        token_ids: Vec<(TokenId, AccountId)>
+    ) {
+        assert_one_yocto();
+        self.assert_not_paused(PauseTarget::Transfer);
+        require!(!token_ids.is_empty(), error::ERR_EMPTY_BATCH);
+
+        let sender_id = env::predecessor_account_id();
+        let transfers = token_ids.iter()
+            .map(|(token_id, receiver_id)| {
+                let (_, _, transfer) = self.transfer_token(
+                    &sender_id,
+                    receiver_id,
+                    token_id,
+                    None,
+                    None
+                );
+                transfer
+            })
+            .collect();
+
+        event::NearEvent::log_nft_transfers(transfers);
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenCore for Contract {
+    #[payable]
+    fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        self.assert_not_paused(PauseTarget::Transfer);
+
+        self.internal_transfer(
+            &env::predecessor_account_id(),
+            &receiver_id,
+            &token_id,
+            approval_id,
+            memo
+        );
+    }
+
+    #[payable]
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        assert_one_yocto();
+        self.assert_not_paused(PauseTarget::Transfer);
+
+        let sender_id = env::predecessor_account_id();
+        let (old_owner_id, old_approvals) = self.internal_transfer(
+            &sender_id,
+            &receiver_id,
+            &token_id,
+            approval_id,
+            memo
+        );
+
+        ext_nft_receiver::nft_on_transfer(
+            sender_id,
+            old_owner_id.clone(),
+            token_id.clone(),
+            msg,
+            receiver_id.clone(),
+            NO_DEPOSIT,
+            env::prepaid_gas()
+                - GAS_FOR_NFT_TRANSFER_CALL
+                - GAS_FOR_RESOLVE_TRANSFER,
+        )
+        .then(ext_self::nft_resolve_transfer(
+            old_owner_id,
+            receiver_id,
+            token_id,
+            old_approvals,
+            env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_TRANSFER,
+        ))
+        .into()
+    }
+
+    fn nft_token(
+        &self,
+        token_id: TokenId
+    ) -> Option<Token> {
+        self.tokens.nft_token(token_id)
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenResolver for Contract {
+    /// the transfer was logged in `nft_transfer_call`,
+    /// only log again if the token is returned
+    #[private]
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<HashMap<AccountId, u64>>,
+    ) -> bool {
+        let receiver_approvals = self.approvals_of(&token_id);
+
+        let success = self.tokens.nft_resolve_transfer(
+            previous_owner_id.clone(),
+            receiver_id.clone(),
+            token_id.clone(),
+            approved_account_ids.clone(),
+        );
+
+        if !success {
+            // receiver's approvals are revoked, previous ones restored
+            self.unindex_approvals(&token_id, receiver_approvals.as_ref());
+            for account_id in approved_account_ids.iter().flat_map(|approvals| approvals.keys()) {
+                self.index_approval(account_id, &token_id);
+            }
+
+            event::NearEvent::log_nft_transfer(
+                receiver_id.to_string(),
+                previous_owner_id.to_string(),
+                vec![token_id],
+                None,
+                None
+            );
+        }
+
+        success
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::royalty::NFTPayouts;
+    use crate::test_utils::{context, setup_token, mint, set_predecessor};
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+    use near_sdk::json_types::U128;
+
+    fn transfer_events() -> Vec<String> {
+        get_logs().into_iter()
+            .filter(|log| log.contains(r#""event":"nft_transfer""#))
+            .collect()
+    }
+
+    #[test]
+    fn direct_transfer() {
+        let mut contract = setup_token();
+
+        set_predecessor(accounts(1));
+        contract.nft_transfer(accounts(2), "0".to_string(), None, None);
+
+        assert_eq!(
+            transfer_events(),
+            vec![r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"old_owner_id":"bob","new_owner_id":"charlie","token_ids":["0"]}]}"#]
+        );
+    }
+
+    #[test]
+    fn approved_transfer() {
+        let mut contract = setup_token();
+        let mut approvals = HashMap::new();
+        approvals.insert(accounts(3), 1);
+        contract.tokens.approvals_by_id.as_mut().unwrap()
+            .insert(&"0".to_string(), &approvals);
+
+        set_predecessor(accounts(3));
+        contract.nft_transfer(accounts(2), "0".to_string(), Some(1), Some("gift".to_string()));
+
+        assert_eq!(
+            transfer_events(),
+            vec![r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"authorized_id":"danny","old_owner_id":"bob","new_owner_id":"charlie","token_ids":["0"],"memo":"gift"}]}"#]
+        );
+    }
+
+    #[test]
+    fn transfer_payout() {
+        let mut contract = setup_token();
+
+        set_predecessor(accounts(1));
+        contract.nft_transfer_payout(
+            accounts(2),
+            "0".to_string(),
+            None,
+            None,
+            U128(1_000),
+            None
+        );
+
+        assert_eq!(
+            transfer_events(),
+            vec![r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"old_owner_id":"bob","new_owner_id":"charlie","token_ids":["0"]}]}"#]
+        );
+    }
+
+    fn resolve_with(contract: &mut Contract, must_revert: bool) -> bool {
+        testing_env!(
+            context(accounts(0)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(must_revert.to_string().into_bytes())]
+        );
+
+        contract.nft_resolve_transfer(
+            accounts(1),
+            accounts(2),
+            "0".to_string(),
+            None
+        )
+    }
+
+    #[test]
+    fn transfer_call_kept() {
+        let mut contract = setup_token();
+
+        set_predecessor(accounts(1));
+        let _ = contract.nft_transfer_call(accounts(2), "0".to_string(), None, None, "".to_string());
+        assert_eq!(
+            transfer_events(),
+            vec![r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"old_owner_id":"bob","new_owner_id":"charlie","token_ids":["0"]}]}"#]
+        );
+
+        assert!(resolve_with(&mut contract, false));
+        assert!(transfer_events().is_empty());
+    }
+
+    #[test]
+    fn transfer_call_returned() {
+        let mut contract = setup_token();
+
+        set_predecessor(accounts(1));
+        let _ = contract.nft_transfer_call(accounts(2), "0".to_string(), None, None, "".to_string());
+
+        assert!(!resolve_with(&mut contract, true));
+        assert_eq!(
+            transfer_events(),
+            vec![r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"old_owner_id":"charlie","new_owner_id":"bob","token_ids":["0"]}]}"#]
+        );
+        assert_eq!(accounts(1), contract.owner_of(&"0".to_string()).unwrap());
+    }
+
+    #[test]
+    fn batch_transfer() {
+        let mut contract = setup_token();
+        mint(&mut contract, "1", accounts(1));
+
+        set_predecessor(accounts(1));
+        contract.nft_batch_transfer(vec![
+            ("0".to_string(), accounts(2)),
+            ("1".to_string(), accounts(3)),
+        ]);
+
+        assert_eq!(
+            transfer_events(),
+            vec![r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"old_owner_id":"bob","new_owner_id":"charlie","token_ids":["0"]},{"old_owner_id":"bob","new_owner_id":"danny","token_ids":["1"]}]}"#]
+        );
+        assert_eq!(accounts(2), contract.owner_of(&"0".to_string()).unwrap());
+        assert_eq!(accounts(3), contract.owner_of(&"1".to_string()).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "E41: empty batch.")]
+    fn empty_batch_transfer() {
+        let mut contract = setup_token();
+        set_predecessor(accounts(1));
+        contract.nft_batch_transfer(vec![]);
+    }
+}
@@ -1,8 +1,11 @@
 use near_sdk::{
     env,
 };
+use near_contract_standards::non_fungible_token::TokenId;
 use core::convert::TryInto;
 
+use crate::error::ERR_TOKEN_NOT_EXIST;
+
 pub fn get_random_number(n: u64) -> u64 {
     let mut seed = env::random_seed();
     let seed_len = seed.len();
@@ -12,3 +15,9 @@ pub fn get_random_number(n: u64) -> u64 {
     let r: u64 = u64::from_le_bytes(arr).try_into().unwrap();
     return r % n;
 }
+
+/// item index of a token, minted tokens are named by their index
+pub fn item_index(token_id: &TokenId) -> u64 {
+    token_id.parse()
+        .unwrap_or_else(|_| env::panic_str(ERR_TOKEN_NOT_EXIST))
+}
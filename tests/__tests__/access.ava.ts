@@ -0,0 +1,77 @@
+import { createWorkspace, assertFailed } from './test_helper';
+
+const workspace = createWorkspace();
+
+workspace.test('grant and revoke role', async (test, {alice, contract}) => {
+  await assertFailed(
+    alice.call(
+      contract,
+      'grant_role',
+      { account_id: alice.accountId, role: 'minter' },
+      { attachedDeposit: '1' }
+    ),
+    'E23: missing role'
+  );
+
+  await contract.call(
+    contract,
+    'grant_role',
+    { account_id: alice.accountId, role: 'admin' },
+    { attachedDeposit: '1' }
+  );
+  test.true(
+    await contract.view('has_role', { account_id: alice.accountId, role: 'admin' })
+  );
+
+  // admin can manage other roles, but not admins
+  await alice.call(
+    contract,
+    'grant_role',
+    { account_id: alice.accountId, role: 'minter' },
+    { attachedDeposit: '1' }
+  );
+  test.deepEqual(
+    await contract.view('roles_of', { account_id: alice.accountId }),
+    ['admin', 'minter']
+  );
+  await assertFailed(
+    alice.call(
+      contract,
+      'revoke_role',
+      { account_id: alice.accountId, role: 'admin' },
+      { attachedDeposit: '1' }
+    ),
+    'E23: missing role'
+  );
+
+  await contract.call(
+    contract,
+    'revoke_role',
+    { account_id: alice.accountId, role: 'admin' },
+    { attachedDeposit: '1' }
+  );
+  test.deepEqual(
+    await contract.view('roles_of', { account_id: alice.accountId }),
+    ['minter']
+  );
+});
+
+workspace.test('two step ownership transfer', async (test, {alice, contract, root}) => {
+  await contract.call(
+    contract,
+    'transfer_ownership',
+    { new_owner_id: alice.accountId },
+    { attachedDeposit: '1' }
+  );
+  test.is(await contract.view('owner'), contract.accountId);
+  test.is(await contract.view('pending_owner'), alice.accountId);
+
+  await assertFailed(
+    root.call(contract, 'accept_ownership', {}, { attachedDeposit: '1' }),
+    'E25: not pending owner'
+  );
+
+  await alice.call(contract, 'accept_ownership', {}, { attachedDeposit: '1' });
+  test.is(await contract.view('owner'), alice.accountId);
+  test.is(await contract.view('pending_owner'), null);
+});
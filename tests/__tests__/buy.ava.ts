@@ -1,4 +1,4 @@
-import { createWorkspace, assertFailed, ONE_NEAR } from './test_helper';
+import { createWorkspace, createContract, assertFailed, ONE_NEAR } from './test_helper';
 import Big from 'big.js';
 
 const workspace = createWorkspace();
@@ -10,6 +10,13 @@ workspace.test('mint without deposit', async (test, {alice, contract}) => {
   );
 });
 
+workspace.test('mint zero boxes', async (test, {alice, contract}) => {
+  await assertFailed(
+    alice.call(contract, 'buy', { n: 0 }, { attachedDeposit: ONE_NEAR.toFixed(0) }),
+    'E21: must buy at least one box'
+  );
+});
+
 workspace.test('mint with just minting fee', async (_, {alice, contract}) => {
   const unitPrice = Big(await contract.view('unit_price'));
   const deposit = unitPrice.times(3)
@@ -90,3 +97,101 @@ workspace.test('pay 1 yN less than required', async (test, {alice, contract}) =>
     'E03: no enough deposit'
   );
 });
+
+workspace.test('bulk discount', async (test, {alice, root}) => {
+  const contract = await createContract(
+    root,
+    'discount1',
+    20,
+    undefined,
+    undefined,
+    {
+      discount_tiers: [
+        { min_boxes: 5, discount: 500 },   // 5% off
+        { min_boxes: 10, discount: 1000 }, // 10% off
+      ]
+    }
+  );
+
+  const small = await contract.view('quote', { n: 4 });
+  test.is(small.discount, '0');
+
+  const quote = await contract.view('quote', { n: 10 });
+  test.is(quote.base_cost, ONE_NEAR.times(10).toFixed(0));
+  test.is(quote.mint_cost, ONE_NEAR.times(9).toFixed(0));
+
+  const cost = Big(await contract.view('cost_for', { n: 10 }));
+  test.is(cost.toFixed(0), quote.total);
+
+  const tokens: [any] = await alice.call(
+    contract,
+    'buy',
+    { n: 10 },
+    {
+      attachedDeposit: cost.toFixed(0)
+    }
+  );
+  test.is(10, tokens.length);
+});
+
+workspace.test('purchase history', async (test, {alice, contract}) => {
+  for (const n of [1, 2]) {
+    const cost = Big(await contract.view('cost_for', { n }));
+    await alice.call(
+      contract,
+      'buy',
+      { n },
+      {
+        attachedDeposit: cost.toFixed(0)
+      }
+    );
+  }
+
+  const purchases: [any] = await contract.view(
+    'purchases_by_account',
+    { account_id: alice.accountId }
+  );
+  test.is(purchases.length, 2);
+  test.is(purchases[0].buyer_id, alice.accountId);
+  test.is(purchases[0].token_ids.length, 1);
+  test.is(purchases[0].price, ONE_NEAR.toFixed(0));
+  test.is(purchases[0].phase, 'open');
+  test.is(purchases[0].currency, 'near');
+  test.is(purchases[1].token_ids.length, 2);
+  test.is(purchases[1].price, ONE_NEAR.times(2).toFixed(0));
+
+  const page = await contract.view(
+    'purchases_by_account',
+    { account_id: alice.accountId, from_index: 1, limit: 1 }
+  );
+  test.is(page.length, 1);
+  test.is(page[0].id, purchases[1].id);
+
+  const tokenId = purchases[1].token_ids[1];
+  const purchase = await contract.view('purchase_of_token', { token_id: tokenId });
+  test.is(purchase.id, purchases[1].id);
+});
+
+workspace.test('sale stats', async (test, {alice, contract}) => {
+  const n = 3;
+  const cost = Big(await contract.view('cost_for', { n }));
+  await alice.call(
+    contract,
+    'buy',
+    { n },
+    {
+      attachedDeposit: cost.toFixed(0)
+    }
+  );
+
+  const stats: any = await contract.view('sale_stats');
+  test.is(stats.total_supply, 10);
+  test.is(stats.minted, 3);
+  test.is(stats.remaining, 7);
+  test.is(stats.total_income, ONE_NEAR.times(3).toFixed(0));
+  test.is(stats.distributed_income, '0');
+  test.is(stats.undistributed_income, ONE_NEAR.times(3).toFixed(0));
+  test.is(stats.unique_buyers, 1);
+  test.is(stats.current_price, ONE_NEAR.toFixed(0));
+  test.is(stats.phase, 'open');
+});
@@ -0,0 +1,65 @@
+import { createContract, createWorkspace, assertFailed } from "./test_helper";
+import Big from "big.js";
+
+const workspace = createWorkspace();
+
+const ONE_DAY = Big(24 * 60 * 60).times(1e9);
+
+workspace.test('tokens are locked after mint', async (test, {alice, root}) => {
+    const contract = await createContract(
+        root,
+        'lock',
+        10,
+        undefined,
+        undefined,
+        {
+            transfer_lock: {
+                duration: { duration: ONE_DAY.toFixed(0) }
+            }
+        }
+    );
+    const bob = await root.createAccount('bob');
+
+    const deposit = Big(await contract.view('cost_for', { n: 1 }));
+    const tokens: [any] = await alice.call(
+        contract,
+        'buy',
+        { n: 1 },
+        {
+            attachedDeposit: deposit.toFixed(0)
+        }
+    );
+    const token = tokens[0];
+
+    const unlockTime = await contract.view('unlock_time', { token_id: token.token_id });
+    test.is(
+        unlockTime,
+        Big(token.metadata.issued_at).plus(ONE_DAY).toFixed(0)
+    );
+
+    await assertFailed(
+        alice.call(
+            contract,
+            'nft_transfer',
+            { receiver_id: bob.accountId, token_id: token.token_id },
+            {
+                attachedDeposit: '1'
+            }
+        ),
+        'E42: token is locked'
+    );
+});
+
+workspace.test('no lock by default', async (test, {alice, contract}) => {
+    const deposit = Big(await contract.view('cost_for', { n: 1 }));
+    const tokens: [any] = await alice.call(
+        contract,
+        'buy',
+        { n: 1 },
+        {
+            attachedDeposit: deposit.toFixed(0)
+        }
+    );
+
+    test.is(await contract.view('unlock_time', { token_id: tokens[0].token_id }), null);
+});
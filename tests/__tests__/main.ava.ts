@@ -1,4 +1,4 @@
-import { createWorkspace } from './test_helper';
+import { createWorkspace, assertFailed } from './test_helper';
 
 const workspace = createWorkspace();
 
@@ -19,3 +19,34 @@ workspace.test('is contract deployed', async (test, {alice, contract, root}) =>
     'nft'
   );
 });
+
+workspace.test('owner updates contract metadata', async (test, {alice, contract}) => {
+  const metadata = {
+    spec: 'nft-1.0.0',
+    name: 'test nft',
+    symbol: 'nft',
+    icon: 'data:image/svg+xml,<svg></svg>',
+    base_uri: 'https://example.com'
+  };
+
+  await assertFailed(
+    alice.call(
+      contract,
+      'set_contract_metadata',
+      { metadata },
+      { attachedDeposit: '1' }
+    ),
+    'E15: only owner can call this method'
+  );
+
+  await contract.call(
+    contract,
+    'set_contract_metadata',
+    { metadata },
+    { attachedDeposit: '1' }
+  );
+
+  const contractMetadata = await contract.view('nft_metadata');
+  test.is(contractMetadata.icon, metadata.icon);
+  test.is(contractMetadata.base_uri, metadata.base_uri);
+});
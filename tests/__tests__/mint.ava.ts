@@ -0,0 +1,78 @@
+import { createContract, createWorkspace, assertFailed, ONE_NEAR } from './test_helper';
+import Big from 'big.js';
+
+const workspace = createWorkspace();
+
+async function createReservedContract(root: any, name: string) {
+  return createContract(
+    root,
+    name,
+    3,
+    undefined,
+    undefined,
+    { reserved: 2 }
+  );
+}
+
+workspace.test('reserved items are not for sale', async (test, {alice, root}) => {
+  const contract = await createReservedContract(root, 'reserved1');
+  test.is(await contract.view('items_for_sale'), 1);
+
+  const cost = Big(await contract.view('cost_for', { n: 2 }));
+  await assertFailed(
+    alice.call(
+      contract,
+      'buy',
+      { n: 2 },
+      {
+        attachedDeposit: cost.toFixed(0)
+      }
+    ),
+    'E01: no enough tokens to draw'
+  );
+});
+
+workspace.test('airdrop reserved items', async (test, {alice, root}) => {
+  const contract = await createReservedContract(root, 'reserved2');
+
+  await assertFailed(
+    alice.call(
+      contract,
+      'airdrop',
+      { receivers: [[alice.accountId, 1]] },
+      {
+        attachedDeposit: ONE_NEAR.toFixed(0)
+      }
+    ),
+    'E23: missing role'
+  );
+
+  await assertFailed(
+    contract.call(
+      contract,
+      'airdrop',
+      { receivers: [[alice.accountId, 3]] },
+      {
+        attachedDeposit: ONE_NEAR.toFixed(0)
+      }
+    ),
+    'E20: no enough reserved items'
+  );
+
+  await contract.call(
+    contract,
+    'airdrop',
+    { receivers: [[alice.accountId, 2]] },
+    {
+      attachedDeposit: ONE_NEAR.toFixed(0)
+    }
+  );
+
+  const tokens = await contract.view(
+    'nft_tokens_for_owner',
+    { account_id: alice.accountId }
+  );
+  test.is(tokens.length, 2);
+  test.is(await contract.view('reserved'), 0);
+  test.is(await contract.view('items_for_sale'), 1);
+});
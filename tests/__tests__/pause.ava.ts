@@ -0,0 +1,61 @@
+import { createWorkspace, assertFailed } from './test_helper';
+import Big from 'big.js';
+
+const workspace = createWorkspace();
+
+workspace.test('pause and unpause buy', async (test, {alice, contract}) => {
+  await assertFailed(
+    alice.call(contract, 'pause', { targets: ['buy'] }, { attachedDeposit: '1' }),
+    'E23: missing role'
+  );
+
+  await contract.call(
+    contract,
+    'pause',
+    { targets: ['buy', 'transfer'] },
+    { attachedDeposit: '1' }
+  );
+  const state = await contract.view('pause_state');
+  test.true(state.buy);
+  test.true(state.transfer);
+  test.false(state.distribute_income);
+  test.false(state.approval);
+
+  const cost = Big(await contract.view('cost_for', { n: 1 }));
+  await assertFailed(
+    alice.call(
+      contract,
+      'buy',
+      { n: 1 },
+      {
+        attachedDeposit: cost.toFixed(0)
+      }
+    ),
+    'E26: buy is paused'
+  );
+
+  await contract.call(
+    contract,
+    'unpause',
+    { targets: ['buy'] },
+    { attachedDeposit: '1' }
+  );
+  const tokens = await alice.call(
+    contract,
+    'buy',
+    { n: 1 },
+    {
+      attachedDeposit: cost.toFixed(0)
+    }
+  );
+
+  await assertFailed(
+    alice.call(
+      contract,
+      'nft_transfer',
+      { receiver_id: contract.accountId, token_id: tokens[0].token_id },
+      { attachedDeposit: '1' }
+    ),
+    'E27: transfer is paused'
+  );
+});
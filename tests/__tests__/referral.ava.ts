@@ -0,0 +1,74 @@
+import { createContract, createWorkspace, assertFailed, ONE_NEAR } from "./test_helper";
+import Big from "big.js";
+
+const workspace = createWorkspace();
+
+async function createReferralContract(root: any, name: string) {
+    return createContract(
+        root,
+        name,
+        10,
+        undefined,
+        undefined,
+        {
+            referral: {
+                rate: 1000, // 10% of purchase
+                cap: ONE_NEAR.mul(0.15).toFixed(0)
+            }
+        }
+    );
+}
+
+workspace.test('self referral', async (test, {alice, root}) => {
+    const contract = await createReferralContract(root, 'referral1');
+    const deposit = Big(await contract.view('cost_for', { n: 1 }));
+
+    await assertFailed(
+        alice.call(
+            contract,
+            'buy',
+            { n: 1, referrer_id: alice.accountId },
+            {
+                attachedDeposit: deposit.toFixed(0)
+            }
+        ),
+        'E13: can not refer yourself'
+    );
+});
+
+workspace.test('referrer earns reward up to cap', async (test, {alice, root}) => {
+    const contract = await createReferralContract(root, 'referral2');
+    const bob = await root.createAccount('bob');
+
+    for (let i = 0; i < 2; i++) {
+        const deposit = Big(await contract.view('cost_for', { n: 1 }));
+        await alice.call(
+            contract,
+            'buy',
+            { n: 1, referrer_id: bob.accountId },
+            {
+                attachedDeposit: deposit.toFixed(0)
+            }
+        );
+    }
+
+    const stats = await contract.view(
+        'referral_stats',
+        { account_id: bob.accountId }
+    );
+    test.is(stats.purchases, 2);
+    test.is(stats.boxes, 2);
+    // 0.1N for the first box, 0.05N left under cap for the second
+    test.is(stats.earned, ONE_NEAR.mul(0.15).toFixed(0));
+    test.is(stats.claimable, stats.earned);
+
+    const oldBalance = Big((await bob.availableBalance()).toBigInt());
+    await bob.call(contract, 'claim_referral_rewards', {});
+    const newBalance = Big((await bob.availableBalance()).toBigInt());
+    test.true(newBalance.gt(oldBalance));
+
+    await assertFailed(
+        bob.call(contract, 'claim_referral_rewards', {}),
+        'E14: no referral reward to claim'
+    );
+});
@@ -1,4 +1,4 @@
-import { createContract, createWorkspace, ONE_NEAR } from "./test_helper";
+import { assertFailed, createContract, createWorkspace, ONE_NEAR } from "./test_helper";
 import Big from "big.js";
 import { NEAR } from "near-workspaces-ava";
 
@@ -118,7 +118,16 @@ workspace.test('income distribution', async (test, {alice, root}) => {
         );
     }
 
-    await alice.call(
+    await assertFailed(
+        alice.call(
+            contract,
+            'distribute_income',
+            {}
+        ),
+        'E23: missing role'
+    );
+
+    await contract.call(
         contract,
         'distribute_income',
         {}
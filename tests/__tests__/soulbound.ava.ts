@@ -0,0 +1,73 @@
+import { createContract, createWorkspace, assertFailed } from "./test_helper";
+import Big from "big.js";
+
+const workspace = createWorkspace();
+
+async function buyBadge(root: any, alice: any, name: string) {
+    const contract = await createContract(
+        root,
+        name,
+        10,
+        undefined,
+        undefined,
+        { soulbound: true }
+    );
+
+    const deposit = Big(await contract.view('cost_for', { n: 1 }));
+    const tokens: [any] = await alice.call(
+        contract,
+        'buy',
+        { n: 1 },
+        {
+            attachedDeposit: deposit.toFixed(0)
+        }
+    );
+
+    return { contract, tokenId: tokens[0].token_id };
+}
+
+workspace.test('soulbound tokens can not be transferred or approved', async (test, {alice, root}) => {
+    const { contract, tokenId } = await buyBadge(root, alice, 'badge1');
+    const bob = await root.createAccount('bob');
+
+    test.true(await contract.view('is_soulbound'));
+
+    await assertFailed(
+        alice.call(
+            contract,
+            'nft_transfer',
+            { receiver_id: bob.accountId, token_id: tokenId },
+            {
+                attachedDeposit: '1'
+            }
+        ),
+        'E43: token is soulbound'
+    );
+
+    await assertFailed(
+        alice.call(
+            contract,
+            'nft_approve',
+            { token_id: tokenId, account_id: bob.accountId },
+            {
+                attachedDeposit: Big(10).pow(22).toFixed(0)
+            }
+        ),
+        'E43: token is soulbound'
+    );
+});
+
+workspace.test('owner burns soulbound token', async (test, {alice, root}) => {
+    const { contract, tokenId } = await buyBadge(root, alice, 'badge2');
+
+    await alice.call(
+        contract,
+        'nft_burn',
+        { token_id: tokenId },
+        {
+            attachedDeposit: '1'
+        }
+    );
+
+    test.is(await contract.view('nft_token', { token_id: tokenId }), null);
+});
@@ -29,7 +29,8 @@ export async function createContract (
   name: string,
   len: number = 10, 
   royalties?: Royalty, 
-  royaltyRate?: number
+  royaltyRate?: number,
+  extraArgs: object = {}
 ) {
   const args = {
     metadata: {
@@ -37,7 +38,8 @@ export async function createContract (
       name: 'test nft',
       symbol: 'nft'
     },
-    len
+    len,
+    ...extraArgs
   };
 
   if (royalties && royaltyRate) {